@@ -2,7 +2,9 @@ use super::element::{Element, ElementClass};
 use std::{
     cell::{Ref, RefCell, RefMut},
     marker::PhantomData,
+    ops::{Index, IndexMut},
     rc::Rc,
+    time::Duration,
 };
 pub use uuid::Uuid as UUID;
 
@@ -23,13 +25,81 @@ macro_rules! attribute_list {
                 $([<$name Array>](Vec<$value>),)*
             }
 
+            impl AttributeType {
+                /// Returns the type of the entries if this is an array type.
+                fn entry_type(self) -> Option<AttributeType> {
+                    match self {
+                        $(AttributeType::[<$name Array>] => Some(AttributeType::$name),)*
+                        _ => None,
+                    }
+                }
+            }
+
             impl AttributeValue {
+                /// Splits an array value into a value for each entry, or gives back a value that isn't an array.
+                fn into_entries(self) -> Result<Vec<AttributeValue>, AttributeValue> {
+                    match self {
+                        $(AttributeValue::[<$name Array>](values) => Ok(values.into_iter().map(AttributeValue::$name).collect()),)*
+                        value => Err(value),
+                    }
+                }
+
+                /// Joins entry values into an array value, returns [None] if an entry isn't the entry type of the array type.
+                fn from_entries(array_type: AttributeType, entries: Vec<AttributeValue>) -> Option<AttributeValue> {
+                    match array_type {
+                        $(AttributeType::[<$name Array>] => entries
+                            .into_iter()
+                            .map(|entry| match entry {
+                                AttributeValue::$name(value) => Some(value),
+                                _ => None,
+                            })
+                            .collect::<Option<Vec<_>>>()
+                            .map(AttributeValue::[<$name Array>]),)*
+                        _ => None,
+                    }
+                }
+
+                /// Reserves capacity for more entries if the value is an array.
+                pub(crate) fn reserve(&mut self, additional: usize) {
+                    match self {
+                        $(AttributeValue::[<$name Array>](values) => values.reserve(additional),)*
+                        _ => {}
+                    }
+                }
+
                 pub fn attribute_type(&self) -> AttributeType {
                     match self {
                         $(AttributeValue::$name(_) => AttributeType::$name,)*
                         $(AttributeValue::[<$name Array>](_) => AttributeType::[<$name Array>],)*
                     }
                 }
+
+                #[cfg(feature = "json")]
+                pub(crate) fn to_json_value(&self) -> serde_json::Value {
+                    match self {
+                        $(AttributeValue::$name(value) => value.to_json_value(),)*
+                        $(AttributeValue::[<$name Array>](values) => serde_json::Value::Array(values.iter().map(ToJsonValue::to_json_value).collect()),)*
+                    }
+                }
+
+                fn approx_eq(&self, other: &AttributeValue, epsilon: f32) -> bool {
+                    match (self, other) {
+                        $((AttributeValue::$name(value), AttributeValue::$name(other)) => value.approx_eq(other, epsilon),)*
+                        $((AttributeValue::[<$name Array>](values), AttributeValue::[<$name Array>](others)) => values.approx_eq(others, epsilon),)*
+                        _ => false,
+                    }
+                }
+
+                fn debug_summary(&self, limit: usize) -> String {
+                    match self {
+                        $(AttributeValue::$name(value) => format!("{}: {:?}", stringify!($name), value),)*
+                        $(AttributeValue::[<$name Array>](values) => array_summary(
+                            stringify!([<$name Array>]),
+                            values.len(),
+                            values.iter().take(limit).map(|value| format!("{value:?}")),
+                        ),)*
+                    }
+                }
             }
 
             $(
@@ -85,18 +155,321 @@ macro_rules! attribute_list {
     };
 }
 
+/// Compares attribute values for [Attribute::approx_eq].
+trait ApproxEq {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool;
+}
+
+macro_rules! approx_eq_exact {
+    ($($value:ty),* $(,)?) => {
+        $(
+            impl ApproxEq for $value {
+                fn approx_eq(&self, other: &Self, _epsilon: f32) -> bool {
+                    self == other
+                }
+            }
+        )*
+    };
+}
+
+approx_eq_exact!(Option<Element>, i32, bool, String, UUID, u64, u8);
+
+macro_rules! approx_eq_fields {
+    ($($value:ty { $($field:tt),* }),* $(,)?) => {
+        $(
+            impl ApproxEq for $value {
+                fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+                    $(self.$field.approx_eq(&other.$field, epsilon))&&*
+                }
+            }
+        )*
+    };
+}
+
+approx_eq_fields!(
+    BinaryBlock { 0 },
+    Time { 0 },
+    Color { red, green, blue, alpha },
+    Vector2 { x, y },
+    Vector3 { x, y, z },
+    Vector4 { x, y, z, w },
+    Angle { pitch, yaw, roll },
+    Quaternion { x, y, z, w },
+);
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self == other || (self.is_nan() && other.is_nan()) || (self - other).abs() <= epsilon
+    }
+}
+
+impl ApproxEq for Matrix {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.0
+            .as_flattened()
+            .iter()
+            .zip(other.0.as_flattened())
+            .all(|(value, other)| value.approx_eq(other, epsilon))
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for Vec<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(value, other)| value.approx_eq(other, epsilon))
+    }
+}
+
+/// Converts an attribute value to JSON for [Element::to_json_value].
+#[cfg(feature = "json")]
+trait ToJsonValue {
+    fn to_json_value(&self) -> serde_json::Value;
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use super::*;
+    use serde_json::Value;
+
+    impl ToJsonValue for Option<Element> {
+        fn to_json_value(&self) -> Value {
+            match self {
+                Some(element) => Value::from(element.get_id().to_string()),
+                None => Value::Null,
+            }
+        }
+    }
+
+    macro_rules! to_json_value_from {
+        ($($value:ty),*) => {
+            $(
+                impl ToJsonValue for $value {
+                    fn to_json_value(&self) -> Value {
+                        Value::from(self.clone())
+                    }
+                }
+            )*
+        };
+    }
+
+    to_json_value_from!(i32, f32, bool, String, u64, u8);
+
+    impl ToJsonValue for BinaryBlock {
+        fn to_json_value(&self) -> Value {
+            Value::from(self.0.iter().map(|byte| format!("{byte:02X}")).collect::<String>())
+        }
+    }
+
+    impl ToJsonValue for UUID {
+        fn to_json_value(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+
+    impl ToJsonValue for Time {
+        fn to_json_value(&self) -> Value {
+            Value::from(self.as_seconds())
+        }
+    }
+
+    impl ToJsonValue for Color {
+        fn to_json_value(&self) -> Value {
+            Value::from(vec![self.red, self.green, self.blue, self.alpha])
+        }
+    }
+
+    impl ToJsonValue for Vector2 {
+        fn to_json_value(&self) -> Value {
+            Value::from(vec![self.x, self.y])
+        }
+    }
+
+    impl ToJsonValue for Vector3 {
+        fn to_json_value(&self) -> Value {
+            Value::from(vec![self.x, self.y, self.z])
+        }
+    }
+
+    impl ToJsonValue for Vector4 {
+        fn to_json_value(&self) -> Value {
+            Value::from(vec![self.x, self.y, self.z, self.w])
+        }
+    }
+
+    impl ToJsonValue for Angle {
+        fn to_json_value(&self) -> Value {
+            Value::from(vec![self.pitch, self.yaw, self.roll])
+        }
+    }
+
+    impl ToJsonValue for Quaternion {
+        fn to_json_value(&self) -> Value {
+            Value::from(vec![self.x, self.y, self.z, self.w])
+        }
+    }
+
+    impl ToJsonValue for Matrix {
+        fn to_json_value(&self) -> Value {
+            Value::Array(self.0.iter().map(|row| Value::from(row.to_vec())).collect())
+        }
+    }
+}
+
+/// Converts a value that isn't an array to another type that isn't an array for [Attribute::convert].
+fn convert_scalar(value: AttributeValue, target: AttributeType) -> Option<AttributeValue> {
+    fn integer(value: &AttributeValue) -> Option<i128> {
+        match value {
+            AttributeValue::Integer(value) => Some(*value as i128),
+            AttributeValue::ULong(value) => Some(*value as i128),
+            AttributeValue::UByte(value) => Some(*value as i128),
+            AttributeValue::Boolean(value) => Some(*value as i128),
+            AttributeValue::Float(value) if value.is_finite() => Some(value.trunc() as i128),
+            AttributeValue::String(value) => {
+                let value = value.trim();
+                value
+                    .parse()
+                    .ok()
+                    .or_else(|| value.parse::<f64>().ok().filter(|value| value.is_finite()).map(|value| value.trunc() as i128))
+            }
+            _ => None,
+        }
+    }
+
+    fn components(value: &AttributeValue) -> Option<Vec<f32>> {
+        match value {
+            AttributeValue::Vector2(value) => Some(vec![value.x, value.y]),
+            AttributeValue::Vector3(value) => Some(vec![value.x, value.y, value.z]),
+            AttributeValue::Vector4(value) => Some(vec![value.x, value.y, value.z, value.w]),
+            _ => None,
+        }
+    }
+
+    if value.attribute_type() == target {
+        return Some(value);
+    }
+
+    let component = |index: usize| components(&value).map(|components| components.get(index).copied().unwrap_or_default());
+
+    Some(match target {
+        AttributeType::Integer => AttributeValue::Integer(i32::try_from(integer(&value)?).ok()?),
+        AttributeType::ULong => AttributeValue::ULong(u64::try_from(integer(&value)?).ok()?),
+        AttributeType::UByte => AttributeValue::UByte(u8::try_from(integer(&value)?).ok()?),
+        AttributeType::Boolean => AttributeValue::Boolean(match &value {
+            AttributeValue::Float(value) => *value != 0.0,
+            AttributeValue::String(text) => match text.trim() {
+                "true" => true,
+                "false" => false,
+                _ => integer(&value)? != 0,
+            },
+            value => integer(value)? != 0,
+        }),
+        AttributeType::Float => AttributeValue::Float(match &value {
+            AttributeValue::Time(value) => value.as_seconds(),
+            AttributeValue::String(value) => value.trim().parse().ok()?,
+            AttributeValue::ObjectId(_) => return None,
+            value => integer(value)? as f32,
+        }),
+        AttributeType::Time => AttributeValue::Time(match &value {
+            AttributeValue::Float(value) => Time::from_seconds(*value)?,
+            AttributeValue::String(value) => Time::from_seconds(value.trim().parse().ok()?)?,
+            _ => return None,
+        }),
+        AttributeType::String => AttributeValue::String(match &value {
+            AttributeValue::Integer(value) => value.to_string(),
+            AttributeValue::Float(value) => value.to_string(),
+            AttributeValue::Boolean(value) => value.to_string(),
+            AttributeValue::ObjectId(value) => value.to_string(),
+            AttributeValue::Time(value) => value.as_seconds().to_string(),
+            AttributeValue::ULong(value) => value.to_string(),
+            AttributeValue::UByte(value) => value.to_string(),
+            _ => return None,
+        }),
+        AttributeType::ObjectId => match &value {
+            AttributeValue::String(value) => AttributeValue::ObjectId(value.trim().parse().ok()?),
+            _ => return None,
+        },
+        AttributeType::Vector2 => AttributeValue::Vector2(Vector2::new(component(0)?, component(1)?)),
+        AttributeType::Vector3 => match &value {
+            AttributeValue::Angle(angle) => AttributeValue::Vector3(Vector3::new(angle.pitch, angle.yaw, angle.roll)),
+            _ => AttributeValue::Vector3(Vector3::new(component(0)?, component(1)?, component(2)?)),
+        },
+        AttributeType::Vector4 => match &value {
+            AttributeValue::Quaternion(quaternion) => AttributeValue::Vector4(Vector4::new(quaternion.x, quaternion.y, quaternion.z, quaternion.w)),
+            _ => AttributeValue::Vector4(Vector4::new(component(0)?, component(1)?, component(2)?, component(3)?)),
+        },
+        AttributeType::Angle => match &value {
+            AttributeValue::Vector3(vector) => AttributeValue::Angle(Angle {
+                pitch: vector.x,
+                yaw: vector.y,
+                roll: vector.z,
+            }),
+            _ => return None,
+        },
+        AttributeType::Quaternion => match &value {
+            AttributeValue::Vector4(vector) => AttributeValue::Quaternion(Quaternion::new(vector.x, vector.y, vector.z, vector.w)),
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+/// The number of array entries shown by [Attribute::summary].
+const SUMMARY_ARRAY_ENTRIES: usize = 8;
+
+fn array_summary(type_name: &str, length: usize, entries: impl Iterator<Item = String>) -> String {
+    let entries = entries.collect::<Vec<_>>().join(", ");
+    if length > SUMMARY_ARRAY_ENTRIES {
+        format!("{type_name}[{length}]: [{entries}, ...]")
+    } else {
+        format!("{type_name}[{length}]: [{entries}]")
+    }
+}
+
 /// A structure that holds raw binary data.
 #[derive(Debug, Clone, Default)]
 pub struct BinaryBlock(pub Vec<u8>);
 
 /// A representation of time in tenths of a millisecond.
+///
+/// The ticks are signed, negative times are stored and written as they are.
+/// [std::time::Duration] can only convert to and from times that are not negative.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use datamodel::attribute::Time;
+///
+/// assert_eq!(Time::from_duration(Duration::from_millis(1500)).unwrap().0, 15000);
+/// assert_eq!(Time(15000).to_duration(), Some(Duration::from_millis(1500)));
+/// assert_eq!(Time(-15000).to_duration(), None);
+/// ```
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Time(pub i32);
 
 impl Time {
+    /// The length of one tick.
+    const TICK: Duration = Duration::from_micros(100);
+
     pub fn as_seconds(&self) -> f32 {
         self.0 as f32 / 10000.0
     }
+
+    /// Converts seconds to the nearest tick, or [None] if the seconds aren't finite or are out of range.
+    pub fn from_seconds(seconds: f32) -> Option<Self> {
+        let ticks = (seconds as f64 * 10000.0).round();
+        (ticks.is_finite() && ticks >= i32::MIN as f64 && ticks <= i32::MAX as f64).then_some(Self(ticks as i32))
+    }
+
+    /// Converts the ticks to a [Duration], or [None] for negative times.
+    pub fn to_duration(&self) -> Option<Duration> {
+        u32::try_from(self.0).ok().map(|ticks| Self::TICK * ticks)
+    }
+
+    /// Converts a [Duration] to the ticks, rounding down to a tenth of a millisecond.
+    ///
+    /// Returns [None] if the duration is longer than the largest time.
+    pub fn from_duration(duration: Duration) -> Option<Self> {
+        i32::try_from(duration.as_micros() / Self::TICK.as_micros()).ok().map(Self)
+    }
 }
 
 /// A structure that 8 bit RGBA color.
@@ -108,6 +481,20 @@ pub struct Color {
     pub alpha: u8,
 }
 
+impl Color {
+    /// Opaque white.
+    pub const WHITE: Self = Self::new(255, 255, 255, 255);
+    /// Opaque black.
+    pub const BLACK: Self = Self::new(0, 0, 0, 255);
+    /// Black with no alpha.
+    pub const TRANSPARENT: Self = Self::new(0, 0, 0, 0);
+
+    /// Creates a color from its channels.
+    pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self { red, green, blue, alpha }
+    }
+}
+
 /// A mathematical 2 dimensional vector.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Vector2 {
@@ -115,6 +502,18 @@ pub struct Vector2 {
     pub y: f32,
 }
 
+impl Vector2 {
+    /// The vector with every component 0.
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+    /// The vector with every component 1.
+    pub const ONE: Self = Self::new(1.0, 1.0);
+
+    /// Creates a vector from its components.
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
 #[cfg(feature = "mint")]
 impl From<mint::Point2<f32>> for Vector2 {
     fn from(v: mint::Point2<f32>) -> Self {
@@ -149,6 +548,8 @@ impl mint::IntoMint for Vector2 {
 }
 
 /// A mathematical 3 dimensional vector.
+///
+/// The direction constants follow Source's coordinate system, where X is forward, Y is left, and Z is up.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Vector3 {
     pub x: f32,
@@ -156,6 +557,24 @@ pub struct Vector3 {
     pub z: f32,
 }
 
+impl Vector3 {
+    /// The vector with every component 0.
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+    /// The vector with every component 1.
+    pub const ONE: Self = Self::new(1.0, 1.0, 1.0);
+    /// The unit vector along positive X, forward in Source.
+    pub const FORWARD: Self = Self::new(1.0, 0.0, 0.0);
+    /// The unit vector along positive Y, left in Source.
+    pub const LEFT: Self = Self::new(0.0, 1.0, 0.0);
+    /// The unit vector along positive Z, up in Source.
+    pub const UP: Self = Self::new(0.0, 0.0, 1.0);
+
+    /// Creates a vector from its components.
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+}
+
 #[cfg(feature = "mint")]
 impl From<mint::Point3<f32>> for Vector3 {
     fn from(v: mint::Point3<f32>) -> Self {
@@ -198,6 +617,18 @@ pub struct Vector4 {
     pub w: f32,
 }
 
+impl Vector4 {
+    /// The vector with every component 0.
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    /// The vector with every component 1.
+    pub const ONE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+
+    /// Creates a vector from its components.
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+}
+
 #[cfg(feature = "mint")]
 impl From<mint::Vector4<f32>> for Vector4 {
     fn from(v: mint::Vector4<f32>) -> Self {
@@ -228,6 +659,29 @@ impl mint::IntoMint for Vector4 {
 }
 
 /// A Tait-Bryan 3 dimensional angle.
+///
+/// The components are in degrees and every serializer stores them in pitch, yaw, roll order.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::{Angle, AttributeInfo}, serializers::{BinarySerializer, KeyValues2Serializer, XmlSerializer}};
+///
+/// let mut root = Element::default();
+/// root.set_attribute("angle", Angle { pitch: 10.0, yaw: 20.0, roll: 30.0 }.into_attribute());
+///
+/// let mut binary = Vec::new();
+/// BinarySerializer::serialize(&mut binary, &Header::default(), &root).unwrap();
+/// let (_, root) = datamodel::deserialize_slice(&binary).unwrap();
+/// let mut text = Vec::new();
+/// KeyValues2Serializer::serialize(&mut text, &Header::default(), &root).unwrap();
+/// let (_, root) = datamodel::deserialize_slice(&text).unwrap();
+/// let mut xml = Vec::new();
+/// XmlSerializer::serialize(&mut xml, &Header::default(), &root).unwrap();
+/// let (_, root) = datamodel::deserialize_slice(&xml).unwrap();
+///
+/// let angle = root.get_attribute("angle").unwrap().as_angle().unwrap();
+/// assert_eq!((angle.pitch, angle.yaw, angle.roll), (10.0, 20.0, 30.0));
+/// ```
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Angle {
     pub pitch: f32,
@@ -272,14 +726,19 @@ pub struct Quaternion {
     pub w: f32,
 }
 
+impl Quaternion {
+    /// The quaternion with no rotation.
+    pub const IDENTITY: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    /// Creates a quaternion from its components.
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+}
+
 impl Default for Quaternion {
     fn default() -> Self {
-        Self {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-            w: 1.0,
-        }
+        Self::IDENTITY
     }
 }
 
@@ -316,7 +775,99 @@ pub struct Matrix(pub [[f32; 4]; 4]);
 
 impl Default for Matrix {
     fn default() -> Self {
-        Self([[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]])
+        Self::IDENTITY
+    }
+}
+
+impl Matrix {
+    /// The matrix with no transformation.
+    pub const IDENTITY: Self = Self([[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+
+    /// Creates a matrix from 16 floats in row-major order.
+    ///
+    /// This is the same order the serializers read and write the matrix in.
+    pub fn from_flat(values: &[f32; 16]) -> Self {
+        Self([
+            [values[0], values[1], values[2], values[3]],
+            [values[4], values[5], values[6], values[7]],
+            [values[8], values[9], values[10], values[11]],
+            [values[12], values[13], values[14], values[15]],
+        ])
+    }
+
+    /// Returns the 16 floats of the matrix in row-major order.
+    ///
+    /// This is the same order the serializers read and write the matrix in.
+    pub fn to_flat(&self) -> [f32; 16] {
+        let mut values = [0.0; 16];
+        for (value, entry) in values.iter_mut().zip(self.0.iter().flatten()) {
+            *value = *entry;
+        }
+        values
+    }
+
+    /// Returns the entry at a row and column, or [None] if either is out of range.
+    pub fn get(&self, row: usize, column: usize) -> Option<f32> {
+        self.0.get(row)?.get(column).copied()
+    }
+
+    /// Sets the entry at a row and column, returns false if either is out of range.
+    pub fn set(&mut self, row: usize, column: usize, value: f32) -> bool {
+        match self.0.get_mut(row).and_then(|entries| entries.get_mut(column)) {
+            Some(entry) => {
+                *entry = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a row of the matrix.
+    ///
+    /// # Panics
+    /// If the row is 4 or more.
+    pub fn row(&self, row: usize) -> [f32; 4] {
+        self.0[row]
+    }
+
+    /// Returns a column of the matrix.
+    ///
+    /// # Panics
+    /// If the column is 4 or more.
+    pub fn column(&self, column: usize) -> [f32; 4] {
+        self.0.map(|row| row[column])
+    }
+
+    /// Returns the matrix with its rows and columns swapped.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::attribute::Matrix;
+    ///
+    /// let mut matrix = Matrix::IDENTITY;
+    /// matrix[(0, 3)] = 5.0;
+    /// assert_eq!(matrix.get(0, 3), Some(5.0));
+    /// assert_eq!(matrix.get(0, 4), None);
+    /// assert_eq!(matrix.transpose().row(3), [5.0, 0.0, 0.0, 1.0]);
+    /// assert_eq!(matrix.column(3), [5.0, 0.0, 0.0, 1.0]);
+    /// ```
+    pub fn transpose(&self) -> Self {
+        Self(std::array::from_fn(|row| self.column(row)))
+    }
+}
+
+/// Indexes the matrix by row and column.
+impl Index<(usize, usize)> for Matrix {
+    type Output = f32;
+
+    fn index(&self, (row, column): (usize, usize)) -> &Self::Output {
+        &self.0[row][column]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut Self::Output {
+        &mut self.0[row][column]
     }
 }
 
@@ -433,6 +984,89 @@ attribute_list! {
     UByte: u8,
 }
 
+impl AttributeType {
+    /// Returns the name of the type used by the text encodings, such as "int" or "vector3_array".
+    pub fn type_name(self) -> &'static str {
+        match self {
+            Self::Element => "element",
+            Self::Integer => "int",
+            Self::Float => "float",
+            Self::Boolean => "bool",
+            Self::String => "string",
+            Self::Binary => "binary",
+            Self::ObjectId => "elementid",
+            Self::Time => "time",
+            Self::Color => "color",
+            Self::Vector2 => "vector2",
+            Self::Vector3 => "vector3",
+            Self::Vector4 => "vector4",
+            Self::Angle => "qangle",
+            Self::Quaternion => "quaternion",
+            Self::Matrix => "matrix",
+            Self::ULong => "uint64",
+            Self::UByte => "uint8",
+            Self::ElementArray => "element_array",
+            Self::IntegerArray => "int_array",
+            Self::FloatArray => "float_array",
+            Self::BooleanArray => "bool_array",
+            Self::StringArray => "string_array",
+            Self::BinaryArray => "binary_array",
+            Self::ObjectIdArray => "elementid_array",
+            Self::TimeArray => "time_array",
+            Self::ColorArray => "color_array",
+            Self::Vector2Array => "vector2_array",
+            Self::Vector3Array => "vector3_array",
+            Self::Vector4Array => "vector4_array",
+            Self::AngleArray => "qangle_array",
+            Self::QuaternionArray => "quaternion_array",
+            Self::MatrixArray => "matrix_array",
+            Self::ULongArray => "uint64_array",
+            Self::UByteArray => "uint8_array",
+        }
+    }
+
+    /// Returns the type with a name used by the text encodings, see [AttributeType::type_name].
+    pub fn from_type_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "element" => Self::Element,
+            "int" => Self::Integer,
+            "float" => Self::Float,
+            "bool" => Self::Boolean,
+            "string" => Self::String,
+            "binary" => Self::Binary,
+            "elementid" => Self::ObjectId,
+            "time" => Self::Time,
+            "color" => Self::Color,
+            "vector2" => Self::Vector2,
+            "vector3" => Self::Vector3,
+            "vector4" => Self::Vector4,
+            "qangle" => Self::Angle,
+            "quaternion" => Self::Quaternion,
+            "matrix" => Self::Matrix,
+            "uint64" => Self::ULong,
+            "uint8" => Self::UByte,
+            "element_array" => Self::ElementArray,
+            "int_array" => Self::IntegerArray,
+            "float_array" => Self::FloatArray,
+            "bool_array" => Self::BooleanArray,
+            "string_array" => Self::StringArray,
+            "binary_array" => Self::BinaryArray,
+            "elementid_array" => Self::ObjectIdArray,
+            "time_array" => Self::TimeArray,
+            "color_array" => Self::ColorArray,
+            "vector2_array" => Self::Vector2Array,
+            "vector3_array" => Self::Vector3Array,
+            "vector4_array" => Self::Vector4Array,
+            "qangle_array" => Self::AngleArray,
+            "quaternion_array" => Self::QuaternionArray,
+            "matrix_array" => Self::MatrixArray,
+            "uint64_array" => Self::ULongArray,
+            "uint8_array" => Self::UByteArray,
+            _ => return None,
+        })
+    }
+}
+
 /// A reference-counted, data that stores a attribute type.
 ///
 /// # Panics
@@ -446,13 +1080,244 @@ impl Attribute {
         Self(Rc::new(RefCell::new(value)))
     }
 
+    /// Creates an element array attribute from elements.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::Attribute};
+    ///
+    /// let children = Attribute::element_array((0..3).map(|_| Element::default()));
+    /// assert_eq!(children.as_element_array().unwrap().len(), 3);
+    /// ```
+    pub fn element_array(elements: impl IntoIterator<Item = Element>) -> Self {
+        elements.into_iter().map(Some).collect()
+    }
+
+    /// Creates an element array attribute from elements that can be null.
+    pub fn nullable_element_array(elements: impl IntoIterator<Item = Option<Element>>) -> Self {
+        elements.into_iter().collect()
+    }
+
     pub fn get_type(&self) -> AttributeType {
         self.0.borrow().attribute_type()
     }
 
+    /// Formats the type and value of the attribute on one line for logging.
+    ///
+    /// Arrays and binary data show their length and only their first entries,
+    /// elements show their class and id instead of their attributes.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::attribute::AttributeInfo;
+    ///
+    /// assert_eq!(1.5f32.into_attribute().summary(), "Float: 1.5");
+    /// let values = (0..1024).map(|value| value as f32).collect::<Vec<_>>().into_attribute();
+    /// assert_eq!(values.summary(), "FloatArray[1024]: [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, ...]");
+    /// assert_eq!(None::<datamodel::Element>.into_attribute().summary(), "Element: null");
+    /// ```
+    pub fn summary(&self) -> String {
+        fn element_summary(element: &Option<Element>) -> String {
+            match element {
+                Some(element) => format!("{} {}", element.get_class(), element.get_id()),
+                None => String::from("null"),
+            }
+        }
+
+        match &*self.get_inner() {
+            AttributeValue::Element(element) => format!("Element: {}", element_summary(element)),
+            AttributeValue::ElementArray(elements) => {
+                array_summary("ElementArray", elements.len(), elements.iter().take(SUMMARY_ARRAY_ENTRIES).map(element_summary))
+            }
+            AttributeValue::Binary(BinaryBlock(bytes)) => array_summary(
+                "Binary",
+                bytes.len(),
+                bytes.iter().take(SUMMARY_ARRAY_ENTRIES).map(|byte| format!("{byte:02X}")),
+            ),
+            value => value.debug_summary(SUMMARY_ARRAY_ENTRIES),
+        }
+    }
+
+    /// Returns if the attributes have the same type and their values are equal, with floats allowed to differ by up to `epsilon`.
+    ///
+    /// Every float in the float, vector, angle, quaternion, and matrix types and their arrays is compared within the tolerance,
+    /// the other types must be exactly equal. Element references are equal when they have the same id.
+    /// NaN is equal to NaN, so values that are NaN on both sides of a round trip still compare equal.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::attribute::{AttributeInfo, Vector3};
+    ///
+    /// let written = Vector3::new(0.1, 0.2, 0.3).into_attribute();
+    /// let read = Vector3::new(0.1000001, 0.2, 0.3).into_attribute();
+    /// assert!(written.approx_eq(&read, 1e-5));
+    /// assert!(!written.approx_eq(&read, 0.0));
+    /// assert!(f32::NAN.into_attribute().approx_eq(&f32::NAN.into_attribute(), 0.0));
+    /// assert!(!1.into_attribute().approx_eq(&1.0f32.into_attribute(), 1e-5));
+    /// ```
+    pub fn approx_eq(&self, other: &Attribute, epsilon: f32) -> bool {
+        self.get_inner().approx_eq(&other.get_inner(), epsilon)
+    }
+
+    /// Converts the value to another type, given by its name in the text encodings such as "float" or "int_array".
+    ///
+    /// Returns [None] if the type name is unknown or the value can't be converted.
+    /// Converting to the same type copies the value.
+    ///
+    /// | From | To | Conversion |
+    /// |---|---|---|
+    /// | int, uint64, uint8, bool | int, uint64, uint8 | [None] if the value doesn't fit, bool is 0 or 1 |
+    /// | float | int, uint64, uint8 | Truncated toward zero, [None] if not finite or the value doesn't fit |
+    /// | int, uint64, uint8, float | bool | False for zero |
+    /// | int, uint64, uint8, bool | float | Rounded to the nearest float |
+    /// | time | float | Seconds |
+    /// | float | time | Seconds rounded to the nearest tenth of a millisecond |
+    /// | int, uint64, uint8, float, bool, time, elementid | string | Formatted, time as seconds |
+    /// | string | int, uint64, uint8, float, bool, time, elementid | Parsed, `true` and `false` for bool, time as seconds, fractions truncated for integers |
+    /// | vector2, vector3, vector4 | vector2, vector3, vector4 | Extra components dropped, missing components zero |
+    /// | vector3, qangle | qangle, vector3 | Pitch, yaw, and roll as x, y, and z |
+    /// | vector4, quaternion | quaternion, vector4 | Component for component |
+    ///
+    /// A value converts to an array of one entry, an array of one entry converts to a value,
+    /// and arrays convert to arrays by converting every entry, if any entry can't be converted the array can't be.
+    /// Every other conversion, such as to or from elements, returns [None].
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::attribute::AttributeInfo;
+    ///
+    /// assert_eq!(2.9f32.into_attribute().convert("int").unwrap().as_i32(), Some(2));
+    /// assert_eq!(String::from(" 42 ").into_attribute().convert("int").unwrap().as_i32(), Some(42));
+    /// assert_eq!(7.into_attribute().convert("int_array").unwrap().get_value::<Vec<i32>>().unwrap().as_slice(), [7]);
+    /// assert!(String::from("text").into_attribute().convert("float").is_none());
+    /// assert!(300.into_attribute().convert("uint8").is_none());
+    /// ```
+    pub fn convert(&self, target: &str) -> Option<Attribute> {
+        let target = AttributeType::from_type_name(target)?;
+        let value = self.get_inner().clone();
+
+        let converted = match (value.into_entries(), target.entry_type()) {
+            (Err(value), None) => convert_scalar(value, target)?,
+            (Err(value), Some(entry_type)) => AttributeValue::from_entries(target, vec![convert_scalar(value, entry_type)?])?,
+            (Ok(entries), Some(entry_type)) => AttributeValue::from_entries(
+                target,
+                entries.into_iter().map(|entry| convert_scalar(entry, entry_type)).collect::<Option<Vec<_>>>()?,
+            )?,
+            (Ok(mut entries), None) if entries.len() == 1 => convert_scalar(entries.pop()?, target)?,
+            (Ok(_), None) => return None,
+        };
+
+        Some(Attribute::new(converted))
+    }
+
     pub fn get_inner(&self) -> Ref<'_, AttributeValue> {
         self.0.borrow()
     }
+
+    /// Returns a mutable reference to the value, shared by every handle to the attribute.
+    ///
+    /// # Panics
+    /// If the value is already borrowed, such as by a [Attribute::get_inner] guard that is still alive.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::attribute::{AttributeInfo, AttributeValue};
+    ///
+    /// let attribute = vec![1, 2, 3].into_attribute();
+    /// let shared = attribute.clone();
+    /// if let AttributeValue::IntegerArray(values) = &mut *attribute.get_inner_mut() {
+    ///     values.push(4);
+    /// }
+    /// assert_eq!(shared.get_value::<Vec<i32>>().unwrap().as_slice(), [1, 2, 3, 4]);
+    /// ```
+    pub fn get_inner_mut(&self) -> RefMut<'_, AttributeValue> {
+        self.0.borrow_mut()
+    }
+
+    /// Returns a reference to the value if the attribute stores the [AttributeInfo] type.
+    pub fn get_value<A: AttributeInfo>(&self) -> Option<Ref<'_, A>> {
+        Ref::filter_map(self.0.borrow(), A::get_inner).ok()
+    }
+
+    /// Returns a reference to the element if the attribute is an element.
+    pub fn as_element(&self) -> Option<Ref<'_, Option<Element>>> {
+        self.get_value()
+    }
+
+    /// Returns a reference to the elements if the attribute is an element array.
+    pub fn as_element_array(&self) -> Option<Ref<'_, [Option<Element>]>> {
+        Ref::filter_map(self.0.borrow(), |value| match value {
+            AttributeValue::ElementArray(elements) => Some(elements.as_slice()),
+            _ => None,
+        })
+        .ok()
+    }
+
+    /// Returns a reference to the string if the attribute is a string.
+    pub fn as_str(&self) -> Option<Ref<'_, str>> {
+        Ref::filter_map(self.0.borrow(), |value| match value {
+            AttributeValue::String(string) => Some(string.as_str()),
+            _ => None,
+        })
+        .ok()
+    }
+}
+
+macro_rules! attribute_copy_accessors {
+    ($($function:ident: $name:ident => $value:ty),* $(,)?) => {
+        impl Attribute {
+            $(
+                #[doc = concat!("Returns a copy of the value if the attribute is a ", stringify!($name), ".")]
+                pub fn $function(&self) -> Option<$value> {
+                    match &*self.0.borrow() {
+                        AttributeValue::$name(value) => Some(*value),
+                        _ => None,
+                    }
+                }
+            )*
+        }
+    };
+}
+
+attribute_copy_accessors! {
+    as_i32: Integer => i32,
+    as_f32: Float => f32,
+    as_bool: Boolean => bool,
+    as_object_id: ObjectId => UUID,
+    as_time: Time => Time,
+    as_color: Color => Color,
+    as_vector2: Vector2 => Vector2,
+    as_vector3: Vector3 => Vector3,
+    as_vector4: Vector4 => Vector4,
+    as_angle: Angle => Angle,
+    as_quaternion: Quaternion => Quaternion,
+    as_matrix: Matrix => Matrix,
+    as_u64: ULong => u64,
+    as_u8: UByte => u8,
+}
+
+impl<A: AttributeInfo> From<A> for Attribute {
+    fn from(value: A) -> Self {
+        value.into_attribute()
+    }
+}
+
+/// Collects values into the array attribute of their type.
+///
+/// # Example
+/// ```
+/// use datamodel::attribute::{Attribute, AttributeType};
+///
+/// let weights: Attribute = [0.25f32, 0.75].into_iter().collect();
+/// assert_eq!(weights.get_type(), AttributeType::FloatArray);
+/// ```
+impl<A> FromIterator<A> for Attribute
+where
+    Vec<A>: AttributeInfo,
+{
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<A>>().into_attribute()
+    }
 }
 
 /// A trait to implement a type that stores as a attribute value.
@@ -489,9 +1354,19 @@ impl<A: AttributeInfo> AttributeVariable<A> {
         {
             owned_attribute
         } else {
-            value.into_attribute()
+            let attribute = value.into_attribute();
+            owner.set_attribute(attribute_name, Attribute::clone(&attribute));
+            attribute
         };
-        owner.set_attribute(attribute_name, Attribute::clone(&attribute));
+        Self {
+            owner,
+            attribute,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps an attribute of the owner that is already known to store `A`.
+    pub(crate) fn from_attribute(owner: Element, attribute: Attribute) -> Self {
         Self {
             owner,
             attribute,
@@ -538,9 +1413,10 @@ impl<E: ElementClass> AttributeElement<E> {
         {
             owned_attribute
         } else {
-            value.map(|e| e.into_element()).into_attribute()
+            let attribute = value.map(|e| e.into_element()).into_attribute();
+            owner.set_attribute(attribute_name, Attribute::clone(&attribute));
+            attribute
         };
-        owner.set_attribute(attribute_name, Attribute::clone(&attribute));
         Self {
             owner,
             attribute,
@@ -591,13 +1467,14 @@ impl<E: ElementClass> AttributeElementArray<E> {
         {
             owned_attribute
         } else {
-            value
+            let attribute = value
                 .into_iter()
                 .map(|a| a.map(|e| e.into_element()))
                 .collect::<Vec<Option<Element>>>()
-                .into_attribute()
+                .into_attribute();
+            owner.set_attribute(attribute_name, Attribute::clone(&attribute));
+            attribute
         };
-        owner.set_attribute(attribute_name, Attribute::clone(&attribute));
         Self {
             owner,
             attribute,
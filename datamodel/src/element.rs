@@ -1,15 +1,21 @@
-use crate::attribute::{Attribute, AttributeValue};
-use indexmap::IndexMap;
+use crate::attribute::{
+    Angle, Attribute, AttributeInfo, AttributeType, AttributeValue, AttributeVariable, BinaryBlock, Color, Matrix, Quaternion, Time, Vector2, Vector3, Vector4,
+};
+use indexmap::{IndexMap, IndexSet};
 use std::{
-    cell::{Ref, RefCell},
+    cell::{Ref, RefCell, RefMut},
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashSet},
     rc::Rc,
 };
+use thiserror::Error as ThisError;
 use uuid::Uuid as UUID;
 
 struct ElementInternal {
-    class: String,
+    class: Rc<str>,
     id: UUID,
     attributes: IndexMap<String, Attribute>,
+    frozen: bool,
 }
 
 /// A reference-counted, structure that stores attributes.
@@ -19,15 +25,26 @@ struct ElementInternal {
 /// # Panics
 /// Borrowing rules from [RefCell] apply:
 /// operations may panic if runtime borrow rules are violated
+///
+/// Operations that modify the element panic if the element is [frozen](Element::freeze).
+///
+/// # Cycles
+/// Elements are reference counted, so elements that reference each other in a cycle are never dropped, even after every other handle is gone.
+/// Call [Element::drop_graph] before dropping the last handle to a graph that could have cycles.
 #[derive(Clone)]
 pub struct Element(Rc<RefCell<ElementInternal>>);
 
+thread_local! {
+    static DEFAULT_CLASS: RefCell<Rc<str>> = RefCell::new(Rc::from(Element::class_name()));
+}
+
 impl Default for Element {
     fn default() -> Self {
         Self(Rc::new(RefCell::new(ElementInternal {
-            class: String::from(Element::class_name()),
+            class: Rc::from(Element::class_name()),
             id: UUID::new_v4(),
             attributes: IndexMap::new(),
+            frozen: false,
         })))
     }
 }
@@ -80,6 +97,23 @@ impl std::fmt::Debug for Element {
     }
 }
 
+/// Iterates over a snapshot of the attributes in the element.
+///
+/// The names and attribute handles are cloned into a list before iterating,
+/// so the element can be modified in the loop but each iteration costs a string allocation per attribute.
+impl IntoIterator for &Element {
+    type Item = (String, Attribute);
+    type IntoIter = std::vec::IntoIter<(String, Attribute)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.get_attributes()
+            .iter()
+            .map(|(attribute_name, attribute)| (attribute_name.clone(), Attribute::clone(attribute)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
 impl ElementClass for Element {
     fn class_name() -> &'static str {
         "DmElement"
@@ -94,29 +128,243 @@ impl ElementClass for Element {
     }
 }
 
+/// A class that an [Element] can be created with, see [Element::new].
+///
+/// This is implemented for the string types that converted into a [String] when classes were stored as one, such as `&String`,
+/// and for [`Rc<str>`](Rc) so elements can share a class that is already allocated.
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+///
+/// use datamodel::Element;
+///
+/// let class = String::from("DmeModel");
+/// assert_eq!(&*Element::new(&class).get_class(), "DmeModel");
+/// assert_eq!(&*Element::new(class).get_class(), "DmeModel");
+///
+/// let shared: Rc<str> = Rc::from("DmeDag");
+/// let first = Element::new(Rc::clone(&shared));
+/// let second = Element::new(&shared);
+/// assert!(Rc::ptr_eq(&first.class_shared(), &second.class_shared()));
+/// ```
+pub trait IntoClass {
+    /// Converts the value into the shared class string.
+    fn into_class(self) -> Rc<str>;
+}
+
+impl IntoClass for Rc<str> {
+    fn into_class(self) -> Rc<str> {
+        self
+    }
+}
+
+impl IntoClass for &Rc<str> {
+    fn into_class(self) -> Rc<str> {
+        Rc::clone(self)
+    }
+}
+
+impl IntoClass for &str {
+    fn into_class(self) -> Rc<str> {
+        Rc::from(self)
+    }
+}
+
+impl IntoClass for &mut str {
+    fn into_class(self) -> Rc<str> {
+        Rc::from(&*self)
+    }
+}
+
+impl IntoClass for String {
+    fn into_class(self) -> Rc<str> {
+        Rc::from(self)
+    }
+}
+
+impl IntoClass for &String {
+    fn into_class(self) -> Rc<str> {
+        Rc::from(self.as_str())
+    }
+}
+
+impl IntoClass for Box<str> {
+    fn into_class(self) -> Rc<str> {
+        Rc::from(self)
+    }
+}
+
+impl IntoClass for std::borrow::Cow<'_, str> {
+    fn into_class(self) -> Rc<str> {
+        Rc::from(self)
+    }
+}
+
+impl IntoClass for char {
+    fn into_class(self) -> Rc<str> {
+        Rc::from(self.to_string())
+    }
+}
+
 impl Element {
     /// Creates a empty element with specified class.
-    pub fn new(class: impl Into<String>) -> Self {
-        Self(Rc::new(RefCell::new(ElementInternal {
-            class: class.into(),
-            id: UUID::new_v4(),
-            attributes: IndexMap::new(),
-        })))
+    ///
+    /// The class is shared, so elements created from the same [Rc] don't each store a copy of it.
+    /// An empty class is replaced with the [Element] class, "DmElement".
+    pub fn new(class: impl IntoClass) -> Self {
+        Self::full(class, UUID::new_v4())
     }
 
     /// Creates a empty element with a specified class and UUID.
-    pub fn full(class: impl Into<String>, id: UUID) -> Self {
+    ///
+    /// An empty class is replaced with the [Element] class, "DmElement".
+    pub fn full(class: impl IntoClass, id: UUID) -> Self {
         Self(Rc::new(RefCell::new(ElementInternal {
-            class: class.into(),
+            class: class_or_default(class.into_class()),
             id,
             attributes: IndexMap::new(),
+            frozen: false,
         })))
     }
 
+    /// Creates a empty element with a specified class and UUID, if no element in the registry has the UUID.
+    ///
+    /// The UUID is added to the registry, so elements created with the same registry never share an id.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, ElementRegistry, attribute::UUID};
+    ///
+    /// let mut registry = ElementRegistry::default();
+    /// let id = UUID::new_v4();
+    /// assert!(Element::create_checked(&mut registry, "DmElement", id).is_ok());
+    /// assert!(Element::create_checked(&mut registry, "DmeModel", id).is_err());
+    /// ```
+    pub fn create_checked(registry: &mut ElementRegistry, class: impl IntoClass, id: UUID) -> Result<Self, DuplicateElementIdError> {
+        if !registry.register(id) {
+            return Err(DuplicateElementIdError(id));
+        }
+        Ok(Self::full(class, id))
+    }
+
+    /// Creates a empty element with the [default class](Element::default_class) and a "name" attribute.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// Element::set_default_class("DmeDag");
+    /// let element = Element::named("body");
+    /// assert_eq!(&*element.get_class(), "DmeDag");
+    /// assert_eq!(element.name_owned().as_deref(), Some("body"));
+    /// // Only the named constructors use the default class.
+    /// assert_eq!(&*Element::default().get_class(), "DmElement");
+    ///
+    /// Element::set_default_class("");
+    /// assert_eq!(&*Element::default_class(), "DmElement");
+    /// ```
+    pub fn named(name: impl Into<String>) -> Self {
+        let mut element = Self::new(Self::default_class());
+        element.set_attribute("name", name.into().into_attribute());
+        element
+    }
+
+    /// Creates a [ElementBuilder] for an element with the [default class](Element::default_class) and a "name" attribute.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// Element::set_default_class("DmeDag");
+    /// let element = Element::named_builder("body").attr("visible", true).build();
+    /// assert_eq!(&*element.get_class(), "DmeDag");
+    /// assert_eq!(element.name_owned().as_deref(), Some("body"));
+    /// assert_eq!(element.get_attributes().len(), 2);
+    /// ```
+    pub fn named_builder(name: impl Into<String>) -> ElementBuilder {
+        Self::builder(Self::default_class()).name(name)
+    }
+
+    /// Returns the class used by [Element::named] and [Element::named_builder] on the current thread.
+    ///
+    /// This is "DmElement" unless changed with [Element::set_default_class].
+    /// [Element::default] always uses "DmElement".
+    pub fn default_class() -> Rc<str> {
+        DEFAULT_CLASS.with(|class| Rc::clone(&class.borrow()))
+    }
+
+    /// Sets the class used by [Element::named] and [Element::named_builder] on the current thread.
+    ///
+    /// An empty class resets it to "DmElement".
+    pub fn set_default_class(class: impl IntoClass) {
+        DEFAULT_CLASS.with(|default_class| *default_class.borrow_mut() = class_or_default(class.into_class()));
+    }
+
+    /// Creates a [ElementBuilder] to construct an element with attributes.
+    ///
+    /// # Example
+    /// ```
+    /// let element = datamodel::Element::builder("DmeModel").name("body").attr("Length", 42).attr("Size", 1.5f32).build();
+    /// assert_eq!(element.get_attributes().len(), 3);
+    /// ```
+    pub fn builder(class: impl IntoClass) -> ElementBuilder {
+        ElementBuilder { element: Self::new(class) }
+    }
+
     /// Gets the class of the element.
-    pub fn get_class(&'_ self) -> Ref<'_, String> {
+    ///
+    /// The returned [Ref] borrows the element, so it must be dropped before the element is mutated.
+    /// Use [Element::class_owned] when the class needs to outlive the borrow.
+    ///
+    /// This returns `Ref<str>` and not `Ref<String>` as it did before classes were interned.
+    /// Code that took `&String` from it can take `&str` instead, or use [Element::class_owned].
+    pub fn get_class(&'_ self) -> Ref<'_, str> {
         let element_data = self.0.borrow();
-        Ref::map(element_data, |element| &element.class)
+        Ref::map(element_data, |element| &*element.class)
+    }
+
+    /// Returns a shared handle to the class of the element.
+    ///
+    /// Deserializers intern classes, so every element read from a file with the same class shares one allocation.
+    ///
+    /// # Example
+    /// ```
+    /// use std::rc::Rc;
+    ///
+    /// use datamodel::{Header, Serializer, serializers::KeyValues2Serializer};
+    ///
+    /// let mut file: &[u8] = b"<!-- dmx encoding keyvalues2 4 format dmx 22 -->
+    /// \"DmeVertexData\"
+    /// {
+    ///     \"first\" \"DmeVertexData\" {}
+    ///     \"second\" \"DmeVertexData\" {}
+    /// }
+    /// ";
+    /// let (_, encoding, version) = Header::from_buffer(&mut file).unwrap();
+    /// let root = KeyValues2Serializer::deserialize(&mut file, encoding, version).unwrap();
+    /// let first = root.get_attribute("first").unwrap().as_element().unwrap().clone().unwrap();
+    /// let second = root.get_attribute("second").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert!(Rc::ptr_eq(&root.class_shared(), &first.class_shared()));
+    /// assert!(Rc::ptr_eq(&first.class_shared(), &second.class_shared()));
+    /// ```
+    pub fn class_shared(&self) -> Rc<str> {
+        Rc::clone(&self.0.borrow().class)
+    }
+
+    /// Returns a copy of the class of the element.
+    pub fn class_owned(&self) -> String {
+        self.get_class().to_string()
+    }
+
+    /// Returns a copy of the "name" attribute of the element.
+    ///
+    /// Returns [None] if the element has no "name" attribute or it isn't a string.
+    pub fn name_owned(&self) -> Option<String> {
+        match &*self.get_attribute("name")?.get_inner() {
+            AttributeValue::String(name) => Some(name.clone()),
+            _ => None,
+        }
     }
 
     /// Sets the class of the element to the class of the [ElementClass].
@@ -125,9 +373,18 @@ impl Element {
     }
 
     /// Sets the class of the element with a string.
-    pub fn set_class_name(&mut self, class: impl Into<String>) {
-        let mut element_data = self.0.borrow_mut();
-        element_data.class = class.into();
+    ///
+    /// Tools reject elements without a class, so an empty class is replaced with the [Element] class, "DmElement".
+    ///
+    /// # Example
+    /// ```
+    /// let mut element = datamodel::Element::new("DmeModel");
+    /// element.set_class_name("");
+    /// assert_eq!(&*element.get_class(), "DmElement");
+    /// ```
+    pub fn set_class_name(&mut self, class: impl IntoClass) {
+        let mut element_data = self.borrow_mut();
+        element_data.class = class_or_default(class.into_class());
     }
 
     /// Gets the UUID identifier of the element.
@@ -138,7 +395,7 @@ impl Element {
 
     /// Sets the UUID identifier of the element.
     pub fn set_id(&mut self, id: UUID) {
-        let mut element_data = self.0.borrow_mut();
+        let mut element_data = self.borrow_mut();
         element_data.id = id;
     }
 
@@ -148,17 +405,250 @@ impl Element {
         self.0.borrow().attributes.get(attribute_name).cloned()
     }
 
+    /// Gets the [Attribute] stored in the element by the name, for attributes that are known to exist.
+    ///
+    /// The element is behind a [RefCell], so `Index` can't return a reference into it, the returned handle shares the attribute instead.
+    /// Use [Element::get_attribute] when the attribute might be missing.
+    ///
+    /// # Panics
+    /// If the element has no attribute with the name.
+    ///
+    /// # Example
+    /// ```
+    /// let element = datamodel::Element::builder("DmElement").attr("Length", 42).build();
+    /// assert_eq!(element.must_get("Length").as_i32(), Some(42));
+    /// ```
+    #[track_caller]
+    pub fn must_get(&self, name: impl AsRef<str>) -> Attribute {
+        let attribute_name = name.as_ref();
+        match self.get_attribute(attribute_name) {
+            Some(attribute) => attribute,
+            None => panic!("Element \"{}\" Has No Attribute \"{}\"", self.get_id(), attribute_name),
+        }
+    }
+
+    /// Returns a copy of the value of the attribute if it exists and stores the [AttributeInfo] type.
+    ///
+    /// The value is copied out of the attribute, so unlike [Attribute::get_value] nothing stays borrowed.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::{AttributeInfo, Vector3}};
+    ///
+    /// let mut element = Element::default();
+    /// element.set_attribute("count", 3.into_attribute());
+    /// element.set_attribute("origin", Vector3 { x: 1.0, y: 2.0, z: 3.0 }.into_attribute());
+    ///
+    /// assert_eq!(element.get_copied::<i32>("count"), Some(3));
+    /// assert_eq!(element.get_copied::<Vector3>("origin").map(|origin| origin.y), Some(2.0));
+    /// assert_eq!(element.get_copied::<f32>("count"), None);
+    /// assert_eq!(element.get_copied::<i32>("missing"), None);
+    /// ```
+    pub fn get_copied<V: AttributeInfo + Copy>(&self, name: impl AsRef<str>) -> Option<V> {
+        self.get_attribute(name)?.get_value::<V>().map(|value| *value)
+    }
+
     /// Removes the attribute from the element and returns it.
     pub fn remove_attribute(&mut self, name: impl AsRef<str>) -> Option<Attribute> {
-        let mut element_data = self.0.borrow_mut();
+        let mut element_data = self.borrow_mut();
         let attribute_name = name.as_ref();
         element_data.attributes.shift_remove(attribute_name)
     }
 
+    /// Removes the attribute from the element and returns it with the position it was at.
+    ///
+    /// Passing the position to [Element::insert_attribute_at] puts the attribute back where it was.
+    pub fn remove_attribute_indexed(&mut self, name: impl AsRef<str>) -> Option<(usize, Attribute)> {
+        let mut element_data = self.borrow_mut();
+        let attribute_name = name.as_ref();
+        element_data
+            .attributes
+            .shift_remove_full(attribute_name)
+            .map(|(index, _, attribute)| (index, attribute))
+    }
+
+    /// Sets an attribute in the element at a position, if a existing one attribute then its returned.
+    ///
+    /// An existing attribute is moved to the position. A position past the end places the attribute last.
+    ///
+    /// # Example
+    /// ```
+    /// let mut element = datamodel::Element::builder("DmElement").attr("a", 1).attr("b", 2).attr("c", 3).build();
+    /// let (index, attribute) = element.remove_attribute_indexed("b").unwrap();
+    /// element.insert_attribute_at(index, "b", attribute);
+    /// assert_eq!(element.get_attributes().keys().collect::<Vec<_>>(), ["a", "b", "c"]);
+    /// ```
+    pub fn insert_attribute_at(&mut self, index: usize, name: impl Into<String>, attribute: Attribute) -> Option<Attribute> {
+        let mut element_data = self.borrow_mut();
+        let attribute_name = name.into();
+        let last_index = if element_data.attributes.contains_key(&attribute_name) {
+            element_data.attributes.len() - 1
+        } else {
+            element_data.attributes.len()
+        };
+        element_data.attributes.shift_insert(index.min(last_index), attribute_name, attribute)
+    }
+
+    /// Moves the attributes named in `order` to the front in that sequence.
+    ///
+    /// Attributes not in the list keep their relative order after them, names the element doesn't have are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// let mut element = datamodel::Element::builder("DmElement").name("cube").attr("size", 2).attr("color", 3).build();
+    /// element.reorder_to(&["color", "missing", "name"]);
+    /// assert_eq!(element.get_attributes().keys().collect::<Vec<_>>(), ["color", "name", "size"]);
+    /// ```
+    pub fn reorder_to(&mut self, order: &[&str]) {
+        let mut element_data = self.borrow_mut();
+        let mut position = 0;
+        for attribute_name in order {
+            let Some(index) = element_data.attributes.get_index_of(*attribute_name) else {
+                continue;
+            };
+            if index < position {
+                continue;
+            }
+            element_data.attributes.move_index(index, position);
+            position += 1;
+        }
+    }
+
+    /// Removes every attribute from the element except "name".
+    ///
+    /// The class and id are not attributes, so they are kept as well.
+    ///
+    /// # Example
+    /// ```
+    /// let mut element = datamodel::Element::builder("DmeModel").name("body").attr("Length", 42).build();
+    /// element.clear_attributes();
+    /// assert_eq!(element.get_attributes().len(), 1);
+    /// assert_eq!(element.name_owned().as_deref(), Some("body"));
+    /// ```
+    pub fn clear_attributes(&mut self) {
+        let mut element_data = self.borrow_mut();
+        element_data.attributes.retain(|attribute_name, _| attribute_name == "name");
+    }
+
+    /// Removes every attribute of the element and every element reachable from it, so cycles between them don't keep them alive.
+    ///
+    /// The elements are left empty, this is for tearing down a graph that is no longer used.
+    ///
+    /// # Panics
+    /// If any reachable element is frozen.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let mut first = Element::default();
+    /// let mut second = Element::default();
+    /// first.link("next", &second);
+    /// second.link("next", &first);
+    ///
+    /// let observer = second.clone();
+    /// first.drop_graph();
+    /// drop((first, second));
+    /// assert_eq!(observer.ref_count(), 1);
+    /// ```
+    pub fn drop_graph(&self) {
+        for element in self.reachable_elements() {
+            element.borrow_mut().attributes.clear();
+        }
+    }
+
     /// Sets an attribute in the element, if a existing one attribute then its returned.
     pub fn set_attribute(&mut self, name: impl Into<String>, attribute: Attribute) -> Option<Attribute> {
         let attribute_name = name.into();
-        self.0.borrow_mut().attributes.insert(attribute_name, attribute)
+        self.borrow_mut().attributes.insert(attribute_name, attribute)
+    }
+
+    /// Gets an attribute storing `V`, inserting the value from `default` when it's missing.
+    ///
+    /// An existing attribute of a different type is replaced, the same as [AttributeVariable::initialize_with].
+    ///
+    /// This returns an [AttributeVariable] and not a `RefMut<V>`, as the attribute lives in its own [RefCell] inside the element's [RefCell].
+    /// A `RefMut` of the attribute would need the element borrowed for as long as it's held, which [Element] can't return a guard for.
+    /// Call [AttributeVariable::get_mut] on the result for the `RefMut<V>`.
+    ///
+    /// # Example
+    /// ```
+    /// let mut element = datamodel::Element::default();
+    /// for _ in 0..3 {
+    ///     element.get_or_insert_value("samples", Vec::<i32>::new).get_mut().push(1);
+    /// }
+    /// assert_eq!(element.get_attribute("samples").unwrap().get_value::<Vec<i32>>().unwrap().len(), 3);
+    /// ```
+    pub fn get_or_insert_value<V: AttributeInfo>(&mut self, name: impl Into<String>, default: impl FnOnce() -> V) -> AttributeVariable<V> {
+        let attribute_name = name.into();
+        let attribute = match self.get_attribute(&attribute_name) {
+            Some(attribute) if attribute.get_type() == V::attribute_type() => attribute,
+            _ => {
+                let attribute = default().into_attribute();
+                self.set_attribute(attribute_name, Attribute::clone(&attribute));
+                attribute
+            }
+        };
+        AttributeVariable::from_attribute(Element::clone(self), attribute)
+    }
+
+    /// Sets an attribute in the element unless it collides with data the serializers store themselves.
+    ///
+    /// An "id" attribute can't be an ObjectId as it would be read back as the element id,
+    /// and a "name" attribute must be a String as it's stored in the element table.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, ReservedKeyError, attribute::{Attribute, AttributeInfo, AttributeType, AttributeValue, UUID}};
+    ///
+    /// let mut element = Element::default();
+    /// let result = element.try_set_attribute("name", 42.into_attribute());
+    /// assert!(matches!(result, Err(ReservedKeyError::Name(AttributeType::Integer))));
+    /// assert!(element.get_attribute("name").is_none());
+    ///
+    /// let result = element.try_set_attribute("id", Attribute::new(AttributeValue::ObjectId(UUID::from_u128(1))));
+    /// assert!(matches!(result, Err(ReservedKeyError::Id)));
+    /// assert!(element.get_attribute("id").is_none());
+    ///
+    /// assert!(element.try_set_attribute("name", String::from("root").into_attribute()).is_ok());
+    /// ```
+    pub fn try_set_attribute(&mut self, name: impl Into<String>, attribute: Attribute) -> Result<Option<Attribute>, ReservedKeyError> {
+        let attribute_name = name.into();
+        check_reserved_key(&attribute_name, &attribute)?;
+        Ok(self.set_attribute(attribute_name, attribute))
+    }
+
+    /// Sets every attribute from the pairs while holding a single borrow of the element.
+    ///
+    /// Pairs that [Element::try_set_attribute] would reject are skipped.
+    ///
+    /// # Returns
+    /// The names of the skipped pairs, in order.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::AttributeInfo};
+    ///
+    /// let mut element = Element::default();
+    /// let rejected = element.set_values([
+    ///     (String::from("width"), 2.0f32.into_attribute()),
+    ///     (String::from("name"), 42.into_attribute()),
+    ///     (String::from("height"), 3.0f32.into_attribute()),
+    /// ]);
+    /// assert_eq!(rejected, ["name"]);
+    /// assert_eq!(element.get_attributes().keys().collect::<Vec<_>>(), ["width", "height"]);
+    /// ```
+    pub fn set_values(&mut self, pairs: impl IntoIterator<Item = (String, Attribute)>) -> Vec<String> {
+        let mut element_data = self.borrow_mut();
+        let mut rejected = Vec::new();
+        for (attribute_name, attribute) in pairs {
+            if check_reserved_key(&attribute_name, &attribute).is_err() {
+                rejected.push(attribute_name);
+                continue;
+            }
+            element_data.attributes.insert(attribute_name, attribute);
+        }
+        rejected
     }
 
     /// Returns the reference to all attributes in the element.
@@ -167,10 +657,1386 @@ impl Element {
         Ref::map(element_data, |element| &element.attributes)
     }
 
-    /// Reserve capacity for additional more attributes.
-    pub fn reserve_attributes(&mut self, additional: usize) {
-        let mut element_data = self.0.borrow_mut();
-        element_data.attributes.reserve(additional);
+    /// Returns if the element has no attributes, including "name".
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().attributes.is_empty()
+    }
+
+    /// Returns a copy of the attribute names in order.
+    ///
+    /// Unlike [Element::get_attributes] the element isn't borrowed, so it can be mutated while iterating the names.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::AttributeInfo};
+    ///
+    /// let mut element = Element::new("DmElement");
+    /// assert!(element.is_empty());
+    ///
+    /// element.set_attribute("first", 1.into_attribute());
+    /// element.set_attribute("second", 2.into_attribute());
+    /// for name in element.attribute_names() {
+    ///     element.remove_attribute(name);
+    /// }
+    /// assert!(element.is_empty());
+    /// ```
+    pub fn attribute_names(&self) -> Vec<String> {
+        self.get_attributes().keys().cloned().collect()
+    }
+
+    /// Returns the names of the attributes that match the predicate.
+    pub fn attributes_matching(&self, predicate: impl Fn(&Attribute) -> bool) -> Vec<String> {
+        self.get_attributes()
+            .iter()
+            .filter(|(_, attribute)| predicate(attribute))
+            .map(|(attribute_name, _)| attribute_name.clone())
+            .collect()
+    }
+
+    /// Returns the names of the attributes that store the [AttributeInfo] type.
+    pub fn attribute_names_of<A: AttributeInfo>(&self) -> Vec<String> {
+        self.attributes_matching(|attribute| attribute.get_type() == A::attribute_type())
+    }
+
+    /// Returns an iterator over the attributes that aren't element or element array attributes, in order.
+    ///
+    /// The attributes are copied out first like iterating `&Element`, so the element can be mutated while iterating.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let mut element = Element::builder("DmElement").name("cube").attr("size", 2).build();
+    /// element.link("parent", &Element::default());
+    /// let names = element.scalar_attributes().map(|(name, _)| name).collect::<Vec<_>>();
+    /// assert_eq!(names, ["name", "size"]);
+    /// ```
+    pub fn scalar_attributes(&self) -> std::vec::IntoIter<(String, Attribute)> {
+        self.get_attributes()
+            .iter()
+            .filter(|(_, attribute)| !matches!(attribute.get_type(), AttributeType::Element | AttributeType::ElementArray))
+            .map(|(attribute_name, attribute)| (attribute_name.clone(), Attribute::clone(attribute)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Calls the closure with every attribute in order, holding a single mutable borrow of the element.
+    ///
+    /// The closure can change the attribute or replace it, names and order stay the same.
+    /// Accessing this element inside the closure will panic, including through an element attribute that refers back to it.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::AttributeInfo};
+    ///
+    /// let mut element = Element::builder("DmElement").attr("width", 2.0f32).attr("height", 3.0f32).build();
+    /// element.for_each_attribute_mut(|_, attribute| {
+    ///     if let Some(value) = attribute.as_f32() {
+    ///         *attribute = (value * 2.0).into_attribute();
+    ///     }
+    /// });
+    /// assert_eq!(element.get_attribute("height").unwrap().as_f32(), Some(6.0));
+    /// ```
+    pub fn for_each_attribute_mut(&self, mut f: impl FnMut(&str, &mut Attribute)) {
+        let mut element_data = self.borrow_mut();
+        for (attribute_name, attribute) in element_data.attributes.iter_mut() {
+            f(attribute_name, attribute);
+        }
+    }
+
+    /// Applies a batch of attribute changes while holding a single borrow of the element.
+    ///
+    /// No other reads of the element can happen until the closure returns, so a half applied change is never seen.
+    /// Accessing the element itself inside the closure will panic.
+    pub fn update<R>(&mut self, f: impl FnOnce(&mut AttributeMapGuard) -> R) -> R {
+        let mut element_data = self.borrow_mut();
+        let mut guard = AttributeMapGuard {
+            attributes: &mut element_data.attributes,
+        };
+        f(&mut guard)
+    }
+
+    /// Replaces every attribute with what the closure returns for it, or drops it when the closure returns [None].
+    ///
+    /// Attributes keep their order, if a returned name is already used the later attribute replaces the earlier one in its position.
+    /// With `recursive` every element reachable from this one is transformed as well,
+    /// elements that only become reachable through the transformation are not visited.
+    ///
+    /// # Example
+    /// ```
+    /// let mut element = datamodel::Element::builder("DmElement").attr("pos", 1).attr("size", 2).build();
+    /// element.transform_attributes(false, |name, attribute| match name {
+    ///     "pos" => Some((String::from("position"), attribute)),
+    ///     _ => Some((name.to_string(), attribute)),
+    /// });
+    /// assert_eq!(element.get_attributes().keys().collect::<Vec<_>>(), ["position", "size"]);
+    /// ```
+    pub fn transform_attributes(&mut self, recursive: bool, mut transform: impl FnMut(&str, Attribute) -> Option<(String, Attribute)>) {
+        let elements = if recursive {
+            self.reachable_elements()
+        } else {
+            IndexSet::from([Element::clone(self)])
+        };
+
+        for element in elements {
+            let attributes = std::mem::take(&mut element.borrow_mut().attributes);
+            let mut transformed_attributes = IndexMap::with_capacity(attributes.len());
+            for (attribute_name, attribute) in attributes {
+                if let Some((new_name, new_attribute)) = transform(&attribute_name, attribute) {
+                    transformed_attributes.insert(new_name, new_attribute);
+                }
+            }
+            element.borrow_mut().attributes = transformed_attributes;
+        }
+    }
+
+    /// Converts every ObjectId and ObjectId array attribute of the element and the elements reachable from it to strings of the UUID,
+    /// returning how many attributes were converted.
+    ///
+    /// ObjectId can only be written by binary versions before 3, which reuse its type byte for [Time](crate::attribute::Time).
+    /// A UUID has no meaning as a time, so the value is kept as its hyphenated text instead,
+    /// which lets files loaded from those versions be written by any version and encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, UUID}, serializers::BinarySerializer};
+    ///
+    /// let mut root = Element::default();
+    /// root.set_attribute("target", UUID::nil().into_attribute());
+    /// assert!(BinarySerializer::serialize(&mut Vec::new(), &Header::default(), &root).is_err());
+    ///
+    /// assert_eq!(root.migrate_object_ids(), 1);
+    /// assert_eq!(*root.get_attribute("target").unwrap().get_value::<String>().unwrap(), "00000000-0000-0000-0000-000000000000");
+    /// assert!(BinarySerializer::serialize(&mut Vec::new(), &Header::default(), &root).is_ok());
+    /// ```
+    pub fn migrate_object_ids(&mut self) -> usize {
+        let mut migrated = 0;
+        self.transform_attributes(true, |attribute_name, attribute| {
+            let migrated_attribute = match &*attribute.get_inner() {
+                AttributeValue::ObjectId(value) => Some(value.to_string().into_attribute()),
+                AttributeValue::ObjectIdArray(values) => Some(values.iter().map(UUID::to_string).collect::<Vec<_>>().into_attribute()),
+                _ => None,
+            };
+            let attribute = match migrated_attribute {
+                Some(migrated_attribute) => {
+                    migrated += 1;
+                    migrated_attribute
+                }
+                None => attribute,
+            };
+            Some((attribute_name.to_string(), attribute))
+        });
+        migrated
+    }
+
+    /// Finds the first element in an element array attribute with a matching "name" attribute.
+    ///
+    /// Returns [None] if the attribute doesn't exist or isn't an element array.
+    pub fn find_child_by_name(&self, attribute_name: impl AsRef<str>, name: impl AsRef<str>) -> Option<Element> {
+        let attribute = self.get_attribute(attribute_name)?;
+        let AttributeValue::ElementArray(children) = &*attribute.get_inner() else {
+            return None;
+        };
+
+        children
+            .iter()
+            .flatten()
+            .find(|child| child.name_owned().is_some_and(|child_name| child_name == name.as_ref()))
+            .cloned()
+    }
+
+    /// Returns the "name" attribute of every element in an element array attribute.
+    ///
+    /// Null entries are skipped and elements without a string name give an empty string.
+    /// Returns [None] if the attribute doesn't exist or isn't an element array.
+    pub fn child_names(&self, attribute_name: impl AsRef<str>) -> Option<Vec<String>> {
+        let attribute = self.get_attribute(attribute_name)?;
+        let AttributeValue::ElementArray(children) = &*attribute.get_inner() else {
+            return None;
+        };
+
+        Some(children.iter().flatten().map(|child| child.name_owned().unwrap_or_default()).collect())
+    }
+
+    /// Returns an iterator over the entries of an element array attribute.
+    ///
+    /// The entries are copied out first, so the element can be mutated while iterating.
+    /// Returns [None] if the attribute doesn't exist or isn't an element array.
+    pub fn iter_array_elements(&self, attribute_name: impl AsRef<str>) -> Option<std::vec::IntoIter<Option<Element>>> {
+        let attribute = self.get_attribute(attribute_name)?;
+        let AttributeValue::ElementArray(children) = &*attribute.get_inner() else {
+            return None;
+        };
+
+        Some(children.clone().into_iter())
+    }
+
+    /// Swaps two entries of an element array attribute in place.
+    ///
+    /// Returns false if the attribute doesn't exist, isn't an element array, or either index is out of range.
+    pub fn swap_array_elements(&mut self, attribute_name: impl AsRef<str>, first: usize, second: usize) -> bool {
+        let element_data = self.borrow_mut();
+        let Some(attribute) = element_data.attributes.get(attribute_name.as_ref()) else {
+            return false;
+        };
+        let AttributeValue::ElementArray(values) = &mut *attribute.get_inner_mut() else {
+            return false;
+        };
+
+        if first >= values.len() || second >= values.len() {
+            return false;
+        }
+        values.swap(first, second);
+        true
+    }
+
+    /// Moves an entry of an element array attribute to another index in place, shifting the entries between them.
+    ///
+    /// Returns false if the attribute doesn't exist, isn't an element array, or either index is out of range.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::Attribute};
+    ///
+    /// let mut element = Element::default();
+    /// element.set_attribute("children", Attribute::element_array(["a", "b", "c"].map(Element::named)));
+    /// assert!(element.move_array_element("children", 2, 0));
+    /// assert_eq!(element.child_names("children").unwrap(), ["c", "a", "b"]);
+    /// assert!(element.swap_array_elements("children", 0, 2));
+    /// assert_eq!(element.child_names("children").unwrap(), ["b", "a", "c"]);
+    /// assert!(!element.move_array_element("children", 3, 0));
+    /// ```
+    pub fn move_array_element(&mut self, attribute_name: impl AsRef<str>, from: usize, to: usize) -> bool {
+        let element_data = self.borrow_mut();
+        let Some(attribute) = element_data.attributes.get(attribute_name.as_ref()) else {
+            return false;
+        };
+        let AttributeValue::ElementArray(values) = &mut *attribute.get_inner_mut() else {
+            return false;
+        };
+
+        if from >= values.len() || to >= values.len() {
+            return false;
+        }
+        if from < to {
+            values[from..=to].rotate_left(1);
+        } else {
+            values[to..=from].rotate_right(1);
+        }
+        true
+    }
+
+    /// Removes a child from an element or element array attribute by id.
+    ///
+    /// An element attribute referencing the child is set to null and every matching entry of an element array is removed.
+    /// Returns if anything was removed.
+    ///
+    /// # Example
+    /// The attribute can reference the element itself.
+    /// ```
+    /// let mut element = datamodel::Element::default();
+    /// element.link("self", &element.clone());
+    /// assert!(element.detach_child("self", &element.clone()));
+    /// assert!(element.get_attribute("self").unwrap().as_element().unwrap().is_none());
+    /// ```
+    pub fn detach_child(&mut self, attribute_name: impl AsRef<str>, child: &Element) -> bool {
+        let attribute = {
+            let element_data = self.borrow_mut();
+            match element_data.attributes.get(attribute_name.as_ref()) {
+                Some(attribute) => Attribute::clone(attribute),
+                None => return false,
+            }
+        };
+        let child_id = *child.get_id();
+        let is_child = |value: &Option<Element>| value.as_ref().is_some_and(|value| *value.get_id() == child_id);
+
+        match &mut *attribute.get_inner_mut() {
+            AttributeValue::Element(value) => {
+                if is_child(value) {
+                    *value = None;
+                    return true;
+                }
+                false
+            }
+            AttributeValue::ElementArray(values) => {
+                let length = values.len();
+                values.retain(|value| !is_child(value));
+                values.len() != length
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets an element attribute referencing another element, if a existing one attribute then its returned.
+    ///
+    /// Only the handle is stored, so elements can reference each other in cycles.
+    /// Serializers collect each element once by id, so a cycle is written as element references and read back the same.
+    /// Elements in a cycle keep each other alive after the last outside handle is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, serializers::BinarySerializer};
+    ///
+    /// let mut parent = Element::named("parent");
+    /// let mut child = Element::named("child");
+    /// parent.link("child", &child);
+    /// child.link("parent", &parent);
+    ///
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize(&mut buffer, &Header::default(), &parent).unwrap();
+    /// let (_, root) = datamodel::deserialize_slice(&buffer).unwrap();
+    /// let child = root.get_attribute("child").unwrap().as_element().unwrap().clone().unwrap();
+    /// let parent = child.get_attribute("parent").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_eq!(parent, root);
+    /// ```
+    pub fn link(&mut self, attribute_name: impl Into<String>, element: &Element) -> Option<Attribute> {
+        self.set_attribute(attribute_name, Some(Element::clone(element)).into_attribute())
+    }
+
+    /// Sets the root of another model as an element attribute, if a existing one attribute then its returned.
+    ///
+    /// Elements are identified by their id when serialized, if the two models share ids only one of the elements
+    /// with each id is written and every reference to that id points to it.
+    /// Call [Element::reassign_ids_recursive] on the other root first when the models could share ids,
+    /// such as when the same file is loaded twice.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let loaded = Element::builder("DmeModel").name("model").build();
+    /// let mut loaded_again = Element::full("DmeModel", *loaded.get_id());
+    /// loaded_again.reassign_ids_recursive();
+    ///
+    /// let mut root = Element::default();
+    /// root.adopt("first", loaded.clone());
+    /// root.adopt("second", loaded_again.clone());
+    ///
+    /// let first = root.get_attribute("first").unwrap().as_element().unwrap().clone().unwrap();
+    /// let second = root.get_attribute("second").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_eq!(first, loaded);
+    /// assert_eq!(second, loaded_again);
+    /// assert_ne!(*first.get_id(), *second.get_id());
+    /// ```
+    pub fn adopt(&mut self, attribute_name: impl Into<String>, other_root: Element) -> Option<Attribute> {
+        self.set_attribute(attribute_name, Some(other_root).into_attribute())
+    }
+
+    /// Appends the entries of an element array attribute to an element array attribute of this element.
+    ///
+    /// The attribute is created if the element doesn't have it.
+    /// Returns false if either attribute isn't an element array.
+    /// The same id collisions as [Element::adopt] apply.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::AttributeInfo};
+    ///
+    /// let first = Element::new("DmeModel");
+    /// let second = Element::new("DmeModel");
+    ///
+    /// let mut root = Element::default();
+    /// assert!(root.merge_arrays("children", &vec![Some(first.clone())].into_attribute()));
+    /// assert!(root.merge_arrays("children", &vec![Some(second.clone())].into_attribute()));
+    /// assert_eq!(&*root.get_attribute("children").unwrap().as_element_array().unwrap(), [Some(first), Some(second)]);
+    ///
+    /// root.set_attribute("count", 1.into_attribute());
+    /// assert!(!root.merge_arrays("count", &Vec::<Option<Element>>::new().into_attribute()));
+    /// assert!(!root.merge_arrays("children", &1.into_attribute()));
+    /// ```
+    pub fn merge_arrays(&mut self, attribute_name: impl AsRef<str>, other: &Attribute) -> bool {
+        let Some(entries) = other.as_element_array().map(|entries| entries.to_vec()) else {
+            return false;
+        };
+
+        let mut element_data = self.borrow_mut();
+        let Some(attribute) = element_data.attributes.get(attribute_name.as_ref()) else {
+            element_data.attributes.insert(attribute_name.as_ref().to_string(), entries.into_attribute());
+            return true;
+        };
+
+        match &mut *attribute.get_inner_mut() {
+            AttributeValue::ElementArray(values) => {
+                values.extend(entries);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Gives the element and every element reachable from it a new random id.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let first = Element::default();
+    /// let mut second = Element::full("DmElement", *first.get_id());
+    /// assert_eq!(first, second);
+    /// second.reassign_ids_recursive();
+    /// assert_ne!(first, second);
+    /// ```
+    pub fn reassign_ids_recursive(&mut self) {
+        for element in self.reachable_elements() {
+            element.borrow_mut().id = UUID::new_v4();
+        }
+    }
+
+    /// Copies the element and every element reachable from it, with new ids.
+    ///
+    /// References between copied elements point at the copies, other attribute values are copied.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let mut root = Element::named("root");
+    /// let mut first = Element::named("first");
+    /// let second = Element::named("second");
+    /// first.link("sibling", &second);
+    /// root.link("first", &first);
+    /// root.link("second", &second);
+    ///
+    /// let copy = root.clone_subtree();
+    /// assert_ne!(copy, root);
+    /// let first_copy = copy.get_attribute("first").unwrap().as_element().unwrap().clone().unwrap();
+    /// let second_copy = copy.get_attribute("second").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_ne!(first_copy, first);
+    /// assert_ne!(second_copy, second);
+    /// assert_eq!(second_copy.name_owned().as_deref(), Some("second"));
+    /// assert_eq!(*first_copy.get_attribute("sibling").unwrap().as_element().unwrap(), Some(second_copy));
+    /// ```
+    pub fn clone_subtree(&self) -> Element {
+        let elements = self.reachable_elements();
+        Self::copy_elements(&elements, &vec![true; elements.len()])
+    }
+
+    /// Copies the element like [Element::clone_subtree], but shares the elements the rest of a document also references.
+    ///
+    /// An element reachable from this one is shared with the original when an element reachable from `document_root`,
+    /// but not from this one, references it. Everything only shared elements reach is shared too.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let mut document = Element::named("document");
+    /// let material = Element::named("material");
+    /// document.link("material", &material);
+    ///
+    /// let mut group = Element::named("group");
+    /// let mut first = Element::named("first");
+    /// let second = Element::named("second");
+    /// first.link("sibling", &second);
+    /// first.link("material", &material);
+    /// group.link("first", &first);
+    /// document.link("group", &group);
+    ///
+    /// let copy = group.clone_subtree_within(&document);
+    /// let first_copy = copy.get_attribute("first").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_ne!(first_copy, first);
+    /// // The sibling is only reachable through the group, so it is copied.
+    /// let second_copy = first_copy.get_attribute("sibling").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_ne!(second_copy, second);
+    /// // The document references the material outside the group, so it is shared.
+    /// assert_eq!(*first_copy.get_attribute("material").unwrap().as_element().unwrap(), Some(material));
+    /// ```
+    pub fn clone_subtree_within(&self, document_root: &Element) -> Element {
+        let elements = self.reachable_elements();
+        let references = elements
+            .iter()
+            .map(|element| {
+                let mut indices = Vec::new();
+                for attribute in element.get_attributes().values() {
+                    match &*attribute.get_inner() {
+                        AttributeValue::Element(Some(child)) => indices.extend(elements.get_index_of(child)),
+                        AttributeValue::ElementArray(children) => indices.extend(children.iter().flatten().filter_map(|child| elements.get_index_of(child))),
+                        _ => {}
+                    }
+                }
+                indices
+            })
+            .collect::<Vec<_>>();
+
+        let mut copied = vec![true; elements.len()];
+        if let Some(index) = elements.get_index_of(document_root) {
+            copied[index] = index == 0;
+        }
+        for outside in document_root.reachable_elements().iter().filter(|element| !elements.contains(*element)) {
+            for attribute in outside.get_attributes().values() {
+                match &*attribute.get_inner() {
+                    AttributeValue::Element(Some(child)) => {
+                        if let Some(index) = elements.get_index_of(child) {
+                            copied[index] = index == 0;
+                        }
+                    }
+                    AttributeValue::ElementArray(children) => {
+                        for index in children.iter().flatten().filter_map(|child| elements.get_index_of(child)) {
+                            copied[index] = index == 0;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..elements.len() {
+                if copied[index] {
+                    continue;
+                }
+                for &child in &references[index] {
+                    if child != 0 && copied[child] {
+                        copied[child] = false;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        Self::copy_elements(&elements, &copied)
+    }
+
+    /// Copies the elements that are marked copied, the element itself is first and always copied.
+    fn copy_elements(elements: &IndexSet<Element>, copied: &[bool]) -> Element {
+        let mut copies = elements
+            .iter()
+            .zip(copied)
+            .map(|(element, &copied)| copied.then(|| Element::full(Rc::clone(&element.0.borrow().class), UUID::new_v4())))
+            .collect::<Vec<_>>();
+        let remap = |element: &Element| match elements.get_index_of(element).and_then(|index| copies[index].as_ref()) {
+            Some(copy) => Element::clone(copy),
+            None => Element::clone(element),
+        };
+
+        for (element, copy) in elements.iter().zip(&copies) {
+            let Some(copy) = copy else {
+                continue;
+            };
+            let attributes = element
+                .get_attributes()
+                .iter()
+                .map(|(attribute_name, attribute)| {
+                    let value = match &*attribute.get_inner() {
+                        AttributeValue::Element(child) => AttributeValue::Element(child.as_ref().map(remap)),
+                        AttributeValue::ElementArray(children) => {
+                            AttributeValue::ElementArray(children.iter().map(|child| child.as_ref().map(remap)).collect())
+                        }
+                        value => value.clone(),
+                    };
+                    (attribute_name.clone(), Attribute::new(value))
+                })
+                .collect();
+            copy.borrow_mut().attributes = attributes;
+        }
+
+        copies.swap_remove(0).expect("The element itself is always copied")
+    }
+
+    /// Reserve capacity for additional more attributes.
+    pub fn reserve_attributes(&mut self, additional: usize) {
+        let mut element_data = self.borrow_mut();
+        element_data.attributes.reserve(additional);
+    }
+
+    /// Reserve capacity for additional more entries in an array attribute.
+    ///
+    /// Does nothing if the attribute is missing or isn't an array.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::{AttributeInfo, Vector3}};
+    ///
+    /// let mut mesh = Element::default();
+    /// mesh.set_attribute("positions", Vec::<Vector3>::new().into_attribute());
+    /// mesh.reserve_array("positions", 1024);
+    /// assert!(mesh.get_attribute("positions").unwrap().get_value::<Vec<Vector3>>().unwrap().capacity() >= 1024);
+    /// ```
+    pub fn reserve_array(&mut self, name: impl AsRef<str>, additional: usize) {
+        let element_data = self.borrow_mut();
+        if let Some(attribute) = element_data.attributes.get(name.as_ref()) {
+            attribute.get_inner_mut().reserve(additional);
+        }
+    }
+
+    /// Marks the element as frozen, any operation that modifies the element will panic.
+    ///
+    /// This only guards the element itself, attributes shared with other elements can still be modified through them.
+    ///
+    /// # Example
+    /// ```should_panic
+    /// use datamodel::{Element, attribute::AttributeInfo};
+    ///
+    /// let mut element = Element::builder("DmElement").attr("count", 1).build();
+    /// element.freeze();
+    /// assert!(element.is_frozen());
+    /// assert_eq!(element.get_copied::<i32>("count"), Some(1));
+    ///
+    /// element.set_attribute("count", 2.into_attribute()); // panics
+    /// ```
+    pub fn freeze(&self) {
+        self.0.borrow_mut().frozen = true;
+    }
+
+    /// Marks the element and every element it references as frozen.
+    pub fn freeze_recursive(&self) {
+        for element in self.reachable_elements() {
+            element.freeze();
+        }
+    }
+
+    /// Checks the attribute names of the element and every element it references.
+    ///
+    /// Reports empty names, names with control characters, and names longer than `max_name_length` bytes.
+    /// This is not checked when setting attributes so it must be called before saving if it is wanted.
+    pub fn validate_attribute_names(&self, max_name_length: usize) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        for element in self.reachable_elements() {
+            for attribute_name in element.get_attributes().keys() {
+                if attribute_name.is_empty() {
+                    issues.push(ValidationIssue::EmptyName {
+                        element: Element::clone(&element),
+                    });
+                    continue;
+                }
+
+                if attribute_name.chars().any(char::is_control) {
+                    issues.push(ValidationIssue::ControlCharacter {
+                        attribute: attribute_name.clone(),
+                        element: Element::clone(&element),
+                    });
+                }
+
+                if attribute_name.len() > max_name_length {
+                    issues.push(ValidationIssue::NameTooLong {
+                        attribute: attribute_name.clone(),
+                        element: Element::clone(&element),
+                        length: attribute_name.len(),
+                        max: max_name_length,
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Compares the "name" attribute of two elements, elements without a string name are ordered first.
+    ///
+    /// # Example
+    /// ```
+    /// use std::cmp::Ordering;
+    ///
+    /// use datamodel::Element;
+    ///
+    /// let apple = Element::builder("DmElement").name("apple").build();
+    /// let banana = Element::builder("DmElement").name("banana").build();
+    /// let unnamed = Element::new("DmElement");
+    ///
+    /// assert_eq!(apple.cmp_by_name(&banana), Ordering::Less);
+    /// assert_eq!(unnamed.cmp_by_name(&apple), Ordering::Less);
+    /// assert_eq!(apple.cmp_by_name(&Element::builder("DmeModel").name("apple").build()), Ordering::Equal);
+    /// ```
+    pub fn cmp_by_name(&self, other: &Element) -> Ordering {
+        self.name_owned().cmp(&other.name_owned())
+    }
+
+    /// Compares the class of two elements.
+    ///
+    /// # Example
+    /// ```
+    /// use std::cmp::Ordering;
+    ///
+    /// use datamodel::Element;
+    ///
+    /// let model = Element::builder("DmeModel").name("a").build();
+    /// let dag = Element::builder("DmeDag").name("b").build();
+    ///
+    /// assert_eq!(dag.cmp_by_class(&model), Ordering::Less);
+    /// assert_eq!(model.cmp_by_class(&Element::new("DmeModel")), Ordering::Equal);
+    /// ```
+    pub fn cmp_by_class(&self, other: &Element) -> Ordering {
+        self.get_class().cmp(&other.get_class())
+    }
+
+    /// Sorts elements by a key, elements with an equal key keep their order.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, ElementSortKey};
+    ///
+    /// let mut elements = ["b", "c", "a"].map(|name| Element::builder("DmElement").name(name).build());
+    /// Element::sort_elements(&mut elements, ElementSortKey::Name);
+    /// assert_eq!(elements.map(|element| element.name_owned().unwrap()), ["a", "b", "c"]);
+    /// ```
+    pub fn sort_elements(elements: &mut [Element], key: ElementSortKey) {
+        match key {
+            ElementSortKey::Name => elements.sort_by(Element::cmp_by_name),
+            ElementSortKey::Class => elements.sort_by(Element::cmp_by_class),
+            ElementSortKey::Id => elements.sort_by(|left, right| left.get_id().cmp(&right.get_id())),
+        }
+    }
+
+    /// Converts the attributes of the element to a JSON object for inspection.
+    ///
+    /// Only this element is converted, element attributes become the id of the referenced element or null.
+    /// Binary data becomes a hex string, time becomes seconds, and the other structured values become arrays of their components.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let element = Element::builder("DmElement").name("cube").attr("size", 2).build();
+    /// assert_eq!(element.to_json_value(), serde_json::json!({ "name": "cube", "size": 2 }));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.get_attributes()
+                .iter()
+                .map(|(attribute_name, attribute)| (attribute_name.clone(), attribute.get_inner().to_json_value()))
+                .collect(),
+        )
+    }
+
+    /// Counts the element and element array slots that reference the target, in the element and every element reachable from it.
+    ///
+    /// Each element is visited once, so cycles don't inflate the count. The target is compared by id.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::Attribute};
+    ///
+    /// let mut root = Element::default();
+    /// let mut child = Element::default();
+    /// child.link("parent", &root);
+    /// root.link("child", &child);
+    /// root.set_attribute("children", Attribute::element_array([Element::clone(&child), Element::clone(&child)]));
+    /// assert_eq!(root.reference_count_to(&child), 3);
+    /// assert_eq!(root.reference_count_to(&root), 1);
+    /// ```
+    pub fn reference_count_to(&self, target: &Element) -> usize {
+        self.reachable_elements()
+            .iter()
+            .map(|element| {
+                element
+                    .get_attributes()
+                    .values()
+                    .map(|attribute| match &*attribute.get_inner() {
+                        AttributeValue::Element(value) => (value.as_ref() == Some(target)) as usize,
+                        AttributeValue::ElementArray(values) => values.iter().filter(|value| value.as_ref() == Some(target)).count(),
+                        _ => 0,
+                    })
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// Changes the element and element array slots that reference `old` to reference `new`, in the element and every element reachable from it.
+    /// Returns the number of slots that were changed.
+    ///
+    /// Each element is visited once and `old` is compared by id, the same as [Element::reference_count_to].
+    /// The attributes are changed in place, so other handles to them see `new` too.
+    /// Elements only reachable through `new` aren't visited.
+    ///
+    /// If `old` is the element itself it can't be replaced, as nothing holds it in a slot. Only the slots that reference it are changed,
+    /// the caller has to use `new` as the root from then on.
+    ///
+    /// # Panics
+    /// If a reachable element with a slot that references `old` is frozen.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::Attribute};
+    ///
+    /// let placeholder = Element::new("DmElement");
+    /// let model = Element::new("DmeModel");
+    /// let mut root = Element::default();
+    /// root.link("model", &placeholder);
+    /// root.set_attribute("children", Attribute::element_array([Element::clone(&placeholder), Element::default()]));
+    ///
+    /// assert_eq!(root.replace_references(&placeholder, &model), 2);
+    /// assert_eq!(root.reference_count_to(&placeholder), 0);
+    /// assert_eq!(root.reference_count_to(&model), 2);
+    ///
+    /// let mut cycle = Element::default();
+    /// cycle.link("self", &Element::clone(&cycle));
+    /// assert_eq!(cycle.replace_references(&Element::clone(&cycle), &model), 1);
+    /// ```
+    pub fn replace_references(&self, old: &Element, new: &Element) -> usize {
+        let mut replaced = 0;
+
+        for element in self.reachable_elements() {
+            let references_old = element.get_attributes().values().any(|attribute| match &*attribute.get_inner() {
+                AttributeValue::Element(value) => value.as_ref() == Some(old),
+                AttributeValue::ElementArray(values) => values.iter().any(|value| value.as_ref() == Some(old)),
+                _ => false,
+            });
+            if !references_old {
+                continue;
+            }
+
+            let attributes = element.borrow_mut().attributes.values().cloned().collect::<Vec<_>>();
+            for attribute in attributes {
+                let mut attribute_value = attribute.get_inner_mut();
+                let slots = match &mut *attribute_value {
+                    AttributeValue::Element(value) => std::slice::from_mut(value),
+                    AttributeValue::ElementArray(values) => values.as_mut_slice(),
+                    _ => continue,
+                };
+
+                for slot in slots.iter_mut().filter(|slot| slot.as_ref() == Some(old)) {
+                    *slot = Some(Element::clone(new));
+                    replaced += 1;
+                }
+            }
+        }
+
+        replaced
+    }
+
+    /// Returns every distinct class of the element and the elements reachable from it, sorted.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let mut root = Element::new("DmElement");
+    /// root.link("first", &Element::new("DmeModel"));
+    /// root.link("second", &Element::new("DmeModel"));
+    /// root.link("third", &Element::new("DmeDag"));
+    ///
+    /// let classes = root.used_classes();
+    /// assert_eq!(classes.into_iter().collect::<Vec<_>>(), ["DmElement", "DmeDag", "DmeModel"]);
+    /// ```
+    pub fn used_classes(&self) -> BTreeSet<String> {
+        self.reachable_elements().iter().map(Element::class_owned).collect()
+    }
+
+    /// Returns the attribute names and [type names](AttributeType::type_name) seen on each class, for the element and every element reachable from it.
+    ///
+    /// Attributes of every element with the same class are combined, so an attribute only some of the elements have is still listed.
+    /// When elements of a class store an attribute with different types, its set has every one of them.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::BTreeSet;
+    ///
+    /// use datamodel::Element;
+    ///
+    /// let mut root = Element::builder("DmElement").attr("count", 1).build();
+    /// root.link("first", &Element::builder("DmeModel").attr("scale", 1.0f32).build());
+    /// root.link("second", &Element::builder("DmeModel").attr("scale", 2).attr("visible", true).build());
+    ///
+    /// let schema = root.infer_schema();
+    /// assert_eq!(schema["DmElement"]["count"], BTreeSet::from(["int"]));
+    /// assert_eq!(schema["DmElement"]["first"], BTreeSet::from(["element"]));
+    /// assert_eq!(schema["DmeModel"]["scale"], BTreeSet::from(["float", "int"]));
+    /// assert_eq!(schema["DmeModel"]["visible"], BTreeSet::from(["bool"]));
+    ///
+    /// let conflicts = schema["DmeModel"].iter().filter(|(_, type_names)| type_names.len() > 1).map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(conflicts, ["scale"]);
+    /// ```
+    pub fn infer_schema(&self) -> BTreeMap<String, BTreeMap<String, BTreeSet<&'static str>>> {
+        let mut schema = BTreeMap::<String, BTreeMap<String, BTreeSet<&'static str>>>::new();
+
+        for element in self.reachable_elements() {
+            let class_attributes = schema.entry(element.class_owned()).or_default();
+            for (attribute_name, attribute) in element.get_attributes().iter() {
+                class_attributes
+                    .entry(attribute_name.clone())
+                    .or_default()
+                    .insert(attribute.get_type().type_name());
+            }
+        }
+
+        schema
+    }
+
+    /// Checks that the element and every element reachable from it have a class in `allowed`.
+    ///
+    /// # Errors
+    /// The id and class of every element with a class that isn't allowed, in the order they are reached.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashSet;
+    /// use datamodel::Element;
+    ///
+    /// let mut root = Element::new("DmElement");
+    /// let mesh = Element::new("DmeMesh");
+    /// root.link("mesh", &mesh);
+    ///
+    /// let allowed = HashSet::from([String::from("DmElement")]);
+    /// assert_eq!(root.validate_classes(&allowed), Err(vec![(*mesh.get_id(), String::from("DmeMesh"))]));
+    /// ```
+    pub fn validate_classes(&self, allowed: &HashSet<String>) -> Result<(), Vec<(UUID, String)>> {
+        let violations = self
+            .reachable_elements()
+            .iter()
+            .filter(|element| !allowed.contains(&*element.get_class()))
+            .map(|element| (*element.get_id(), element.class_owned()))
+            .collect::<Vec<_>>();
+
+        if violations.is_empty() { Ok(()) } else { Err(violations) }
+    }
+
+    /// Changes the class of the element and every element reachable from it with the class `from` to `to`, returning how many were changed.
+    ///
+    /// Elements are visited once by id, serializers write the new class the next time the graph is serialized.
+    /// An empty `to` is replaced with "DmElement", the same as [Element::set_class_name].
+    ///
+    /// # Panics
+    /// If a reachable element with the class `from` is frozen.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Element;
+    ///
+    /// let mut root = Element::new("DmeModel");
+    /// let child = Element::new("DmeModel");
+    /// root.link("child", &child);
+    /// root.link("again", &child);
+    ///
+    /// assert_eq!(root.rename_class("DmeModel", "DmeModel2"), 2);
+    /// assert_eq!(&*child.get_class(), "DmeModel2");
+    /// ```
+    pub fn rename_class(&self, from: &str, to: &str) -> usize {
+        let class = class_or_default(Rc::from(to));
+        let mut renamed = 0;
+
+        for element in self.reachable_elements() {
+            if &*element.get_class() != from {
+                continue;
+            }
+            element.borrow_mut().class = Rc::clone(&class);
+            renamed += 1;
+        }
+
+        renamed
+    }
+
+    /// Hashes the element and every element it references into a value that is stable across program runs.
+    ///
+    /// The hash is 64 bit FNV-1a over the class, id, and attributes of each element in the order they are reached.
+    /// Element references are hashed by their id, so each element is only hashed once even with cycles.
+    /// Numbers are hashed as little endian bytes and floats by their bits.
+    /// Attribute types are hashed by their [type name](AttributeType::type_name), so the hash doesn't depend on the order of [AttributeType].
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, attribute::{AttributeInfo, UUID}};
+    ///
+    /// let mut element = Element::full("DmElement", UUID::from_u128(1));
+    /// element.set_attribute("count", 3.into_attribute());
+    /// // The hash is the same on every platform and run.
+    /// assert_eq!(element.content_hash(), 0x482B_188E_0957_808D);
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        self.hash_content(false)
+    }
+
+    /// Same as [Element::content_hash] but elements are hashed in order of id and attributes in order of name.
+    ///
+    /// Graphs with the same content hash the same no matter the order the attributes were inserted in.
+    pub fn content_hash_sorted(&self) -> u64 {
+        self.hash_content(true)
+    }
+
+    /// Returns the number of handles to this element.
+    ///
+    /// The count includes the handles held by attributes of other elements that reference it.
+    pub fn ref_count(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+
+    /// Returns if the element is frozen.
+    pub fn is_frozen(&self) -> bool {
+        self.0.borrow().frozen
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, ElementInternal> {
+        let element_data = self.0.borrow_mut();
+        if element_data.frozen {
+            panic!("Element \"{}\" Is Frozen", element_data.id);
+        }
+        element_data
+    }
+
+    fn hash_content(&self, sorted: bool) -> u64 {
+        let mut elements = self.reachable_elements();
+        if sorted {
+            elements.sort_by(|left, right| left.get_id().cmp(&right.get_id()));
+        }
+
+        let mut hasher = ContentHasher::default();
+        self.get_id().content_hash(&mut hasher);
+
+        for element in &elements {
+            let element_data = element.0.borrow();
+            element_data.class.content_hash(&mut hasher);
+            element_data.id.content_hash(&mut hasher);
+
+            let mut attributes = element_data.attributes.iter().collect::<Vec<_>>();
+            if sorted {
+                attributes.sort_by_key(|(attribute_name, _)| *attribute_name);
+            }
+
+            hasher.write_length(attributes.len());
+            for (attribute_name, attribute) in attributes {
+                attribute_name.content_hash(&mut hasher);
+                attribute.get_inner().content_hash(&mut hasher);
+            }
+        }
+
+        hasher.0
+    }
+
+    /// Collects the element and every element reachable through its attributes.
+    pub(crate) fn reachable_elements(&self) -> IndexSet<Element> {
+        let mut collected_elements = IndexSet::new();
+        let mut collection_stack = vec![Element::clone(self)];
+        collected_elements.insert(Element::clone(self));
+
+        while let Some(collecting_element) = collection_stack.pop() {
+            for attribute in collecting_element.get_attributes().values() {
+                match &*attribute.get_inner() {
+                    AttributeValue::Element(value) => {
+                        if let Some(element) = value
+                            && collected_elements.insert(Element::clone(element))
+                        {
+                            collection_stack.push(Element::clone(element));
+                        }
+                    }
+                    AttributeValue::ElementArray(values) => {
+                        for element in values.iter().flatten() {
+                            if collected_elements.insert(Element::clone(element)) {
+                                collection_stack.push(Element::clone(element));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        collected_elements
+    }
+}
+
+/// Checks an attribute against the element data stored in the element table, see [Element::try_set_attribute].
+fn check_reserved_key(attribute_name: &str, attribute: &Attribute) -> Result<(), ReservedKeyError> {
+    let attribute_type = attribute.get_type();
+    match attribute_name {
+        "id" if attribute_type == AttributeType::ObjectId => Err(ReservedKeyError::Id),
+        "name" if attribute_type != AttributeType::String => Err(ReservedKeyError::Name(attribute_type)),
+        _ => Ok(()),
+    }
+}
+
+fn class_or_default(class: Rc<str>) -> Rc<str> {
+    if class.is_empty() { Rc::from(Element::class_name()) } else { class }
+}
+
+/// Shares class strings between elements created while deserializing.
+#[derive(Default)]
+pub(crate) struct ClassInterner(HashSet<Rc<str>>);
+
+impl ClassInterner {
+    pub(crate) fn intern(&mut self, class: &str) -> Rc<str> {
+        if let Some(interned) = self.0.get(class) {
+            return Rc::clone(interned);
+        }
+
+        let interned = Rc::<str>::from(class);
+        self.0.insert(Rc::clone(&interned));
+        interned
+    }
+}
+
+/// The ids of elements created with [Element::create_checked].
+#[derive(Debug, Clone, Default)]
+pub struct ElementRegistry(HashSet<UUID>);
+
+impl ElementRegistry {
+    /// Returns if the id is in the registry.
+    pub fn contains(&self, id: &UUID) -> bool {
+        self.0.contains(id)
+    }
+
+    /// Adds an id to the registry, returns false if it was already in it.
+    ///
+    /// Use this for the ids of elements that weren't created with [Element::create_checked], such as loaded elements.
+    pub fn register(&mut self, id: UUID) -> bool {
+        self.0.insert(id)
+    }
+}
+
+/// An error returned by [Element::create_checked] when the id is already in the [ElementRegistry].
+#[derive(Debug, ThisError)]
+#[error("Element Id \"{0}\" Is Already Used")]
+pub struct DuplicateElementIdError(pub UUID);
+
+/// A builder to construct an [Element] created by [Element::builder].
+pub struct ElementBuilder {
+    element: Element,
+}
+
+impl ElementBuilder {
+    /// Sets the UUID identifier of the element.
+    pub fn id(mut self, id: UUID) -> Self {
+        self.element.set_id(id);
+        self
+    }
+
+    /// Sets the "name" attribute of the element.
+    pub fn name(self, name: impl Into<String>) -> Self {
+        self.attr("name", name.into())
+    }
+
+    /// Sets an attribute in the element.
+    pub fn attr(mut self, name: impl Into<String>, attribute: impl Into<Attribute>) -> Self {
+        self.element.set_attribute(name, attribute.into());
+        self
+    }
+
+    /// Returns the constructed element.
+    pub fn build(self) -> Element {
+        self.element
+    }
+}
+
+/// The mutable access to the attributes of an element given by [Element::update].
+pub struct AttributeMapGuard<'a> {
+    attributes: &'a mut IndexMap<String, Attribute>,
+}
+
+impl AttributeMapGuard<'_> {
+    /// Gets the [Attribute] stored in the element by the name.
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&Attribute> {
+        self.attributes.get(name.as_ref())
+    }
+
+    /// Sets an attribute in the element, if a existing one attribute then its returned.
+    pub fn set(&mut self, name: impl Into<String>, attribute: Attribute) -> Option<Attribute> {
+        self.attributes.insert(name.into(), attribute)
+    }
+
+    /// Removes the attribute from the element and returns it.
+    pub fn remove(&mut self, name: impl AsRef<str>) -> Option<Attribute> {
+        self.attributes.shift_remove(name.as_ref())
+    }
+
+    /// Renames an attribute keeping its position in the element.
+    ///
+    /// Returns false if the attribute doesn't exist or the new name is already used.
+    pub fn rename(&mut self, name: impl AsRef<str>, new_name: impl Into<String>) -> bool {
+        let new_name = new_name.into();
+        if self.attributes.contains_key(&new_name) {
+            return false;
+        }
+
+        let Some((index, _, attribute)) = self.attributes.shift_remove_full(name.as_ref()) else {
+            return false;
+        };
+        self.attributes.shift_insert(index, new_name, attribute);
+        true
+    }
+}
+
+/// What [Element::sort_elements] orders elements by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementSortKey {
+    /// The "name" attribute, see [Element::cmp_by_name].
+    Name,
+    /// The class, see [Element::cmp_by_class].
+    Class,
+    /// The UUID identifier.
+    Id,
+}
+
+/// An error returned by [Element::try_set_attribute] when the attribute would collide with reserved element data.
+#[derive(Debug, ThisError)]
+pub enum ReservedKeyError {
+    #[error("Attribute \"id\" Can't Be Type ObjectId")]
+    Id,
+    #[error("Attribute \"name\" Must Be Type String: Got {0:?}")]
+    Name(AttributeType),
+}
+
+/// An issue found by [Element::validate_attribute_names].
+#[derive(Debug, ThisError)]
+pub enum ValidationIssue {
+    #[error("Element \"{}\" Has An Attribute With An Empty Name", element.get_id())]
+    EmptyName { element: Element },
+    #[error("Attribute \"{}\" In Element \"{}\" Has A Control Character", attribute.escape_debug(), element.get_id())]
+    ControlCharacter { attribute: String, element: Element },
+    #[error("Attribute \"{}\" In Element \"{}\" Name Is Too Long: Has {} Max {}", attribute, element.get_id(), length, max)]
+    NameTooLong {
+        attribute: String,
+        element: Element,
+        length: usize,
+        max: usize,
+    },
+}
+
+/// A 64 bit FNV-1a hasher, used over [std::hash::DefaultHasher] as its output is not stable between versions.
+struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl ContentHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_length(&mut self, length: usize) {
+        self.write(&(length as u64).to_le_bytes());
+    }
+}
+
+trait ContentHash {
+    fn content_hash(&self, hasher: &mut ContentHasher);
+}
+
+macro_rules! content_hash_bytes {
+    ($($value:ty),* $(,)?) => {
+        $(
+            impl ContentHash for $value {
+                fn content_hash(&self, hasher: &mut ContentHasher) {
+                    hasher.write(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+content_hash_bytes!(i32, u64, u8);
+
+macro_rules! content_hash_fields {
+    ($($value:ty { $($field:ident),* }),* $(,)?) => {
+        $(
+            impl ContentHash for $value {
+                fn content_hash(&self, hasher: &mut ContentHasher) {
+                    $(self.$field.content_hash(hasher);)*
+                }
+            }
+        )*
+    };
+}
+
+content_hash_fields!(
+    Color { red, green, blue, alpha },
+    Vector2 { x, y },
+    Vector3 { x, y, z },
+    Vector4 { x, y, z, w },
+    Angle { pitch, yaw, roll },
+    Quaternion { x, y, z, w },
+);
+
+impl ContentHash for f32 {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        hasher.write(&self.to_bits().to_le_bytes());
+    }
+}
+
+impl ContentHash for bool {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        hasher.write(&[*self as u8]);
+    }
+}
+
+impl ContentHash for str {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        hasher.write_length(self.len());
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl ContentHash for String {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        self.as_str().content_hash(hasher);
+    }
+}
+
+impl ContentHash for BinaryBlock {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        hasher.write_length(self.0.len());
+        hasher.write(&self.0);
+    }
+}
+
+impl ContentHash for UUID {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        hasher.write(self.as_bytes());
+    }
+}
+
+impl ContentHash for Time {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        self.0.content_hash(hasher);
+    }
+}
+
+impl ContentHash for Matrix {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        for value in self.to_flat() {
+            value.content_hash(hasher);
+        }
+    }
+}
+
+impl ContentHash for Option<Element> {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        match self {
+            Some(element) => {
+                hasher.write(&[1]);
+                element.get_id().content_hash(hasher);
+            }
+            None => hasher.write(&[0]),
+        }
+    }
+}
+
+impl<T: ContentHash> ContentHash for Vec<T> {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        hasher.write_length(self.len());
+        for value in self {
+            value.content_hash(hasher);
+        }
+    }
+}
+
+impl ContentHash for AttributeValue {
+    fn content_hash(&self, hasher: &mut ContentHasher) {
+        self.attribute_type().type_name().content_hash(hasher);
+
+        macro_rules! content_hash_values {
+            ($($variant:ident),* $(,)?) => {
+                paste::paste! {
+                    match self {
+                        $(
+                            AttributeValue::$variant(value) => value.content_hash(hasher),
+                            AttributeValue::[<$variant Array>](values) => values.content_hash(hasher),
+                        )*
+                    }
+                }
+            };
+        }
+
+        content_hash_values!(
+            Element, Integer, Float, Boolean, String, Binary, ObjectId, Time, Color, Vector2, Vector3, Vector4, Angle, Quaternion, Matrix, ULong, UByte,
+        );
     }
 }
 
@@ -4,43 +4,63 @@
 //!
 //! # Quick Start
 //! Code to load a dmx file and print the header.
-//! ```
+//! ```no_run
 //! let file = std::fs::File::open("file.dmx").unwrap();
 //! let mut file_buffer = std::io::BufReader::new(file);
 //! let (header, _) = datamodel::deserialize(&mut file_buffer).unwrap();
 //! println!("Dmx file format is {} with version {}.", header.format, header.format_version);
 //! ```
 //! Code to create an Element and serialize it to a buffer.
-//! ```
-//! use datamodel::Serializer;
+//! ```no_run
+//! use datamodel::{Serializer, attribute::AttributeInfo, serializers::BinarySerializer};
 //!
 //! let mut root = datamodel::Element::default();
 //! root.set_attribute("name", String::from("The Angle").into_attribute());
 //! root.set_attribute("rotation", 43.46f32.into_attribute());
 //! let file = std::fs::File::create("file.dmx").unwrap();
 //! let mut buffer = std::io::BufWriter::new(file);
-//! let header = datamodel::Header {
-//!     format: String::from("Rotation"),
-//!     format_version: 7,
-//! };
+//! let header = datamodel::Header::new("Rotation", 7);
 //! BinarySerializer::serialize(&mut buffer, &header, &root).unwrap();
 //! ```
 //!
 //! # Features
 //! - [mint](https://crates.io/crates/mint) Allow for math library interoperability for math attributes.
 //! - [datamodel-derive](https://crates.io/crates/datamodel-derive) A derive marco to implement ElementClass.
+//! - json Inspect elements as [serde_json](https://crates.io/crates/serde_json) values.
+//! - async Read and write files from [tokio](https://crates.io/crates/tokio) async streams.
+//! - gzip Read gzip compressed files with [deserialize] using [flate2](https://crates.io/crates/flate2).
 
 pub mod attribute;
 
 mod element;
+pub use element::AttributeMapGuard;
+pub use element::DuplicateElementIdError;
 pub use element::Element;
+pub use element::ElementBuilder;
 pub use element::ElementClass;
+pub use element::ElementRegistry;
+pub use element::ElementSortKey;
+pub use element::IntoClass;
+pub use element::ReservedKeyError;
+pub use element::ValidationIssue;
 
 pub mod serializers;
 
 mod serializing;
+pub use serializing::EncodingKind;
 pub use serializing::FileHeaderError;
 pub use serializing::Header;
+pub use serializing::HeaderDisplay;
 pub use serializing::SerializationError;
 pub use serializing::Serializer;
 pub use serializing::deserialize;
+pub use serializing::deserialize_all;
+pub use serializing::deserialize_arena;
+#[cfg(feature = "async")]
+pub use serializing::deserialize_async;
+pub use serializing::deserialize_full;
+pub use serializing::deserialize_slice;
+pub use serializing::detect_encoding;
+pub use serializing::is_binary_encoding;
+#[cfg(feature = "async")]
+pub use serializing::serialize_async;
@@ -1,5 +1,6 @@
 use std::{
-    io::{BufRead, Error, Write},
+    collections::HashMap,
+    io::{BufRead, Error, ErrorKind, Read, Seek, SeekFrom, Write},
     str::FromStr,
 };
 
@@ -10,11 +11,39 @@ use uuid::{Error as UUIDError, Uuid as UUID};
 use crate::{
     ElementClass,
     attribute::{Angle, Attribute, AttributeInfo, AttributeType, AttributeValue, BinaryBlock, Color, Matrix, Quaternion, Time, Vector2, Vector3, Vector4},
-    element::Element,
+    element::{ClassInterner, Element},
     serializing::{Header, Serializer},
 };
 
 /// An error returned by [BinarySerializer] from serializing or deserializing.
+///
+/// Offsets are in bytes from the start of the body, the buffer after the header line that is given to [BinarySerializer::deserialize](Serializer::deserialize).
+/// Add the length of the header line, including its newline, for the offset in the file.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, UUID}, serializers::{BinarySerializationError, BinarySerializer}};
+///
+/// // Fixed ids, so the element table can't contain the bytes searched for below.
+/// let mut root = Element::full("DmElement", UUID::from_u128(0xAAAA_AAAA_AAAA_AAAA_AAAA_AAAA_AAAA_AAAA));
+/// root.set_attribute("child", Some(Element::full("DmElement", UUID::from_u128(0xBBBB_BBBB_BBBB_BBBB_BBBB_BBBB_BBBB_BBBB))).into_attribute());
+/// let mut file = Vec::new();
+/// BinarySerializer::serialize(&mut file, &Header::default(), &root).unwrap();
+///
+/// // The element attribute type followed by index 1, the child, which becomes index 7.
+/// let reference = file.windows(5).position(|bytes| bytes == [1, 1, 0, 0, 0]).unwrap();
+/// file[reference + 1] = 7;
+///
+/// let header_length = file.iter().position(|&byte| byte == b'\n').unwrap() + 1;
+/// let mut body = &file[header_length..];
+/// let (_, encoding, version) = Header::from_buffer(&mut file.as_slice()).unwrap();
+/// match BinarySerializer::deserialize(&mut body, encoding, version) {
+///     Err(BinarySerializationError::InvalidElementTableIndex { index: 7, offset, .. }) => {
+///         assert_eq!(header_length + offset as usize, reference + 1);
+///     }
+///     result => panic!("{result:?}"),
+/// }
+/// ```
 #[derive(Debug, ThisError)]
 pub enum BinarySerializationError {
     #[error("Read Buffer Error: \"{0}\"")]
@@ -25,6 +54,8 @@ pub enum BinarySerializationError {
     TooManyStrings { count: usize, max: usize },
     #[error("Too Many Element For Table: Found {} Max {}", count, MAX_ARRAY_SIZE)]
     TooManyElements { count: usize },
+    #[error("Element Table Length Is Over The Limit: Got {} Max {} At Offset {}", length, max, offset)]
+    InvalidElementTableLength { length: usize, max: usize, offset: u64 },
     #[error("Attribute \"name\" In Element \"{}\" Is Not Type String", element.get_id())]
     InvalidNameAttribute { element: Element },
     #[error("Element \"{}\" Has Too Many Attributes: Has {} Max {}", element.get_id(), count, MAX_ARRAY_SIZE)]
@@ -39,20 +70,24 @@ pub enum BinarySerializationError {
     AttributeArrayTooLong { attribute: String, element: Element, count: usize },
     #[error("Deserialize Encoding Is Wrong Encoding")]
     WrongEncoding,
-    #[error("Array Length Was Invalid Length")]
-    InvalidArraySize,
-    #[error("String Table Index Was Invalid")]
-    InvalidStringTableIndex,
-    #[error("Prefix Element Had Element Attribute Which Is Invalid")]
-    InvalidPrefixElementAttribute,
-    #[error("Unknown Attribute Id: Got {}", attribute_id)]
-    UnknownAttribute { attribute_id: i8 },
-    #[error("Invalid Element Table Index: Got {} Size {}", index, size)]
-    InvalidElementTableIndex { index: i32, size: usize },
+    #[error("Array Length Was Invalid Length At Offset {}", offset)]
+    InvalidArraySize { offset: u64 },
+    #[error("String Table Index Was Invalid At Offset {}", offset)]
+    InvalidStringTableIndex { offset: u64 },
+    #[error("Prefix Element Had Element Attribute Which Is Invalid At Offset {}", offset)]
+    InvalidPrefixElementAttribute { offset: u64 },
+    #[error("Unknown Attribute Id: Got {} At Offset {}", attribute_id, offset)]
+    UnknownAttribute { attribute_id: i8, offset: u64 },
+    #[error("Invalid Element Table Index: Got {} Size {} At Offset {}", index, size, offset)]
+    InvalidElementTableIndex { index: i32, size: usize, offset: u64 },
     #[error("Failed To Parse UUID, Error \"{0}\"")]
     UUIDParseError(#[from] UUIDError),
     #[error("No Elements Where Serialized")]
     NoElements,
+    #[error("Checksum Trailer Is Missing")]
+    MissingChecksum,
+    #[error("Checksum Mismatch: Expected {:08X} Found {:08X}", expected, found)]
+    ChecksumMismatch { expected: u32, found: u32 },
 }
 
 /// Valve's Binary encoding Serializer.
@@ -60,6 +95,73 @@ pub enum BinarySerializationError {
 /// Encodes the data in a binary format for smaller file sizes.
 ///
 /// Versions are between 1 and 9.
+/// Older versions can be targeted with [Serializer::serialize_version] for older engines,
+/// but not every attribute type can be stored in every version.
+///
+/// # Element Table
+/// Each element in the table is always stored as class, name, then id, the version only changes how the fields are stored.
+/// Strings are either null terminated inline or an index into the string table.
+///
+/// | Version | Class | Name | Id |
+/// |---|---|---|---|
+/// | 1 | inline string | inline string | 16 byte UUID |
+/// | 2 - 3 | 16 bit index | inline string | 16 byte UUID |
+/// | 4 | 16 bit index | 16 bit index | 16 byte UUID |
+/// | 5 | 32 bit index | 32 bit index | 16 byte UUID |
+/// | 6 - 7 | 32 bit index, then a 32 bit link type that is ignored | 32 bit index | 16 byte UUID |
+/// | 8 - 9 | 32 bit index | 32 bit index | 16 byte UUID |
+///
+/// # UUID Byte Order
+/// Element ids and ObjectId attributes are stored in the Windows GUID layout Valve's tools use,
+/// the first three fields are little endian and the last 8 bytes are in order, the same as [UUID::to_bytes_le].
+/// This is not the RFC 4122 network order, tools that read the 16 bytes big endian see the first three fields swapped.
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::UUID, serializers::BinarySerializer};
+///
+/// let id: UUID = "00112233-4455-6677-8899-aabbccddeeff".parse().unwrap();
+/// let mut buffer = Vec::new();
+/// BinarySerializer::serialize(&mut buffer, &Header::default(), &Element::full("DmElement", id)).unwrap();
+/// let stored = [0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF];
+/// assert!(buffer.windows(16).any(|bytes| bytes == stored));
+/// ```
+///
+/// # Truncated Strings
+/// Strings that end the file without a null terminator are an [UnexpectedEof](std::io::ErrorKind::UnexpectedEof) error instead of losing their last byte.
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::AttributeInfo, serializers::{BinarySerializationError, BinarySerializer}};
+///
+/// let mut root = Element::default();
+/// root.set_attribute("text", String::from("last").into_attribute());
+///
+/// let mut buffer = Vec::new();
+/// BinarySerializer::serialize_version(&mut buffer, &Header::default(), &root, 1).unwrap();
+/// assert_eq!(buffer.pop(), Some(0));
+///
+/// let mut reader = std::io::BufReader::new(buffer.as_slice());
+/// let (_, encoding, version) = Header::from_buffer(&mut reader).unwrap();
+/// let result = BinarySerializer::deserialize(&mut reader, encoding, version);
+/// assert!(matches!(result, Err(BinarySerializationError::BufferError(error)) if error.kind() == std::io::ErrorKind::UnexpectedEof));
+/// ```
+///
+/// # Example
+/// A [Time](crate::attribute::Time) attribute needs at least version 3, so it is rejected before anything is written.
+/// ```
+/// use datamodel::{
+///     Element, Header, Serializer,
+///     attribute::{AttributeInfo, AttributeType, Time},
+///     serializers::{BinarySerializationError, BinarySerializer},
+/// };
+///
+/// assert!(!BinarySerializer::supports(AttributeType::Time, 2));
+///
+/// let mut root = Element::default();
+/// root.set_attribute("start", Time(0).into_attribute());
+///
+/// let mut buffer = Vec::new();
+/// let result = BinarySerializer::serialize_version(&mut buffer, &Header::default(), &root, 2);
+/// assert!(matches!(result, Err(BinarySerializationError::InvalidVersionForAttribute { min: 3, .. })));
+/// assert!(buffer.is_empty());
+/// ```
 pub struct BinarySerializer;
 
 impl Serializer for BinarySerializer {
@@ -74,611 +176,675 @@ impl Serializer for BinarySerializer {
     }
 
     fn serialize_version(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<(), Self::Error> {
-        if !(1..=Self::version()).contains(&version) {
-            return Err(BinarySerializationError::InvalidVersion { version });
-        }
+        write_elements(buffer, header, root, version, BinaryOptions::default(), &mut |_| {}, None)
+    }
 
-        let mut writer = Writer::new(buffer);
-        writer.write_string(&header.create_header(Self::name(), version))?;
+    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
+        Ok(Self::deserialize_table(buffer, encoding, version)?.swap_remove(0))
+    }
 
-        if version >= VERSION_PREFIX_ELEMENT {
-            writer.write_integer(0)?;
-        }
+    fn supports(attribute_type: AttributeType, version: i32) -> bool {
+        let (min, max) = attribute_version_range(attribute_type);
+        (min..=max).contains(&version)
+    }
+}
 
-        let collected_elements = collect_elements(root);
-        let collected_strings = collect_strings(&collected_elements, version);
+impl BinarySerializer {
+    /// Returns the type byte an attribute type is written with in a version, or [None] if the version can't store the type.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{attribute::AttributeType, serializers::BinarySerializer};
+    ///
+    /// assert_eq!(BinarySerializer::attribute_type_byte(AttributeType::Integer, 9), Some(2));
+    /// assert_eq!(BinarySerializer::attribute_type_byte(AttributeType::IntegerArray, 8), Some(16));
+    /// assert_eq!(BinarySerializer::attribute_type_byte(AttributeType::IntegerArray, 9), Some(34));
+    /// assert_eq!(BinarySerializer::attribute_type_from_byte(7, 2), Some(AttributeType::ObjectId));
+    /// assert_eq!(BinarySerializer::attribute_type_from_byte(7, 3), Some(AttributeType::Time));
+    /// ```
+    pub fn attribute_type_byte(attribute_type: AttributeType, version: i32) -> Option<u8> {
+        attribute_type_id(attribute_type, version).map(|attribute_id| attribute_id as u8)
+    }
 
-        let max_string_table_length = if version >= VERSION_GLOBAL_STRING_TABLE {
-            MAX_ARRAY_SIZE
-        } else {
-            MAX_SHORT_ARRAY_SIZE
-        };
-        if collected_strings.len() > max_string_table_length {
-            return Err(BinarySerializationError::TooManyStrings {
-                count: collected_strings.len(),
-                max: max_string_table_length,
-            });
-        }
-        if version >= VERSION_STRING_TABLE {
-            if version >= VERSION_GLOBAL_STRING_TABLE {
-                writer.write_integer(collected_strings.len() as i32)?;
-            } else {
-                writer.write_short(collected_strings.len() as i16)?;
-            }
+    /// Returns the attribute type a type byte is read as in a version, or [None] if the version doesn't define the byte.
+    pub fn attribute_type_from_byte(attribute_byte: u8, version: i32) -> Option<AttributeType> {
+        attribute_type_from_id(attribute_byte as i8, version).map(|(attribute_type, _)| attribute_type)
+    }
 
-            for string in &collected_strings {
-                writer.write_string(string)?;
-            }
-        }
+    /// Decodes the buffer for every element in the element table, in table order.
+    ///
+    /// The root element is first. Elements in the table that nothing references are included.
+    pub fn deserialize_table(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Vec<Element>, BinarySerializationError> {
+        check_encoding(&encoding, version)?;
 
-        if collected_elements.len() > MAX_ARRAY_SIZE {
-            return Err(BinarySerializationError::TooManyElements {
-                count: collected_elements.len(),
-            });
+        let mut elements = Vec::new();
+        read_elements(buffer, version, MAX_ARRAY_SIZE, &mut elements, &mut Recovery::disabled(), &mut |_| None)?;
+
+        if elements.is_empty() {
+            return Err(BinarySerializationError::NoElements);
         }
-        writer.write_integer(collected_elements.len() as i32)?;
-        for element in &collected_elements {
-            if version >= VERSION_STRING_TABLE {
-                writer.write_string_index(element.get_class().as_str(), version, &collected_strings)?;
-            } else {
-                writer.write_string(element.get_class().as_str())?;
-            }
 
-            if (VERSION_LINK_TYPE..VERSION_DEPRECATE_LINK_TYPE).contains(&version) {
-                writer.write_integer(-1)?;
-            }
+        Ok(elements)
+    }
 
-            if let Some(element_name_attribute) = element.get_attribute("name") {
-                if let AttributeValue::String(element_name) = &*element_name_attribute.get_inner() {
-                    if version >= VERSION_GLOBAL_STRING_TABLE {
-                        writer.write_string_index(element_name, version, &collected_strings)?;
-                    } else {
-                        writer.write_string(element_name)?;
-                    }
+    /// Reads the class, name, and id of every element in the element table without decoding any attributes.
+    ///
+    /// This is much faster than [BinarySerializer::deserialize_table] for indexing large files.
+    /// The buffer is only read up to the end of the element table.
+    ///
+    /// # Returns
+    /// The id, class, and name of each element in table order, the root element is first.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, serializers::BinarySerializer};
+    ///
+    /// let mut root = Element::named("root");
+    /// let child = Element::new("DmeModel");
+    /// root.link("child", &child);
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+    ///
+    /// let mut data = buffer.as_slice();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let index = BinarySerializer::read_index(&mut data, encoding, version).unwrap();
+    /// assert_eq!(index[0], (*root.get_id(), String::from("DmElement"), String::from("root")));
+    /// assert_eq!(index[1], (*child.get_id(), String::from("DmeModel"), String::new()));
+    /// ```
+    pub fn read_index(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Vec<(UUID, String, String)>, BinarySerializationError> {
+        check_encoding(&encoding, version)?;
+
+        let mut index = Vec::new();
+        read_element_table(&mut Reader::new(buffer), version, MAX_ARRAY_SIZE, |element_class, element_name, element_id| {
+            index.push((element_id, element_class, element_name));
+        })?;
+
+        Ok(index)
+    }
+
+    /// Finds an array attribute of an element without reading the values of any array into memory.
+    ///
+    /// The attributes before it are skipped over, then [BinarySerializer::read_array_lazy] reads its entries one at a time.
+    /// The buffer must be right after the header, the same as [BinarySerializer::deserialize](Serializer::deserialize).
+    ///
+    /// # Returns
+    /// Where the array is, or [None] if the element has no array attribute with the name.
+    /// Element arrays aren't found, as their entries are indices into the element table.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{BufReader, Cursor};
+    /// use datamodel::{Element, Header, Serializer, attribute::AttributeInfo, serializers::BinarySerializer};
+    ///
+    /// let mut root = Element::default();
+    /// root.set_attribute("weights", vec![0.5f32; 100_000].into_attribute());
+    /// root.set_attribute("positions", vec![1.0f32, 2.0, 3.0].into_attribute());
+    /// let mut file = Vec::new();
+    /// BinarySerializer::serialize(&mut file, &Header::default(), &root).unwrap();
+    ///
+    /// let mut buffer = BufReader::new(Cursor::new(file));
+    /// let (_, encoding, version) = Header::from_buffer(&mut buffer).unwrap();
+    /// let location = BinarySerializer::locate_array(&mut buffer, encoding, version, *root.get_id(), "positions").unwrap().unwrap();
+    /// assert_eq!(location.len, 3);
+    ///
+    /// let sum = BinarySerializer::read_array_lazy(&mut buffer, &location).unwrap().map(|entry| entry.unwrap().as_f32().unwrap()).sum::<f32>();
+    /// assert_eq!(sum, 6.0);
+    /// ```
+    pub fn locate_array(
+        buffer: &mut (impl BufRead + Seek),
+        encoding: String,
+        version: i32,
+        element_id: UUID,
+        attribute_name: &str,
+    ) -> Result<Option<ArrayLocation>, BinarySerializationError> {
+        check_encoding(&encoding, version)?;
+
+        let start = buffer.stream_position()?;
+        let mut reader = Reader::new(buffer);
+        let mut element_ids = Vec::new();
+        let string_table = read_element_table(&mut reader, version, MAX_ARRAY_SIZE, |_, _, element_id| element_ids.push(element_id))?;
+
+        for current_id in element_ids {
+            let attribute_count = array_size_check(reader.read_integer()?, reader.value_offset)?;
+            for _ in 0..attribute_count {
+                let current_name = if version >= VERSION_LARGE_STRING_INDEX {
+                    get_string_table_index(reader.read_integer()?, &string_table, reader.value_offset)?
+                } else if version >= VERSION_STRING_TABLE {
+                    get_string_table_index(reader.read_short()? as i32, &string_table, reader.value_offset)?
                 } else {
-                    return Err(BinarySerializationError::InvalidNameAttribute {
-                        element: Element::clone(element),
-                    });
+                    reader.read_string()?
+                };
+                let attribute_id = reader.read_byte()?;
+
+                if current_id == element_id
+                    && current_name == attribute_name
+                    && let Some((attribute_type, true)) = attribute_type_from_id(attribute_id, version)
+                    && attribute_type != AttributeType::ElementArray
+                {
+                    let len = array_size_check(reader.read_integer()?, reader.value_offset)?;
+                    let entry_id = attribute_id - attribute_array_offset(version);
+                    let (entry_type, _) = attribute_type_from_id(entry_id, version).expect("array types have a single type");
+                    return Ok(Some(ArrayLocation {
+                        position: start + reader.offset,
+                        len,
+                        attribute_type,
+                        entry_type,
+                        entry_id,
+                    }));
                 }
-            } else if version >= VERSION_GLOBAL_STRING_TABLE {
-                writer.write_string_index("", version, &collected_strings)?;
-            } else {
-                writer.write_string("")?;
+
+                reader.skip_attribute(version, attribute_id)?;
             }
 
-            writer.write_uuid(*element.get_id())?;
+            if current_id == element_id {
+                break;
+            }
         }
 
-        for element in &collected_elements {
-            let element_attributes = element.get_attributes();
-            let attribute_count = element_attributes.len() - element_attributes.contains_key("name") as usize;
-            if attribute_count > MAX_ARRAY_SIZE {
-                return Err(BinarySerializationError::TooManyAttributes {
-                    element: Element::clone(element),
-                    count: attribute_count,
-                });
-            }
-            writer.write_integer(attribute_count as i32)?;
+        Ok(None)
+    }
 
-            for (attribute_name, attribute_value) in element_attributes.iter() {
-                if attribute_name == "name" {
-                    continue;
-                }
+    /// Seeks to an array found with [BinarySerializer::locate_array] and returns an iterator that reads its entries from the buffer as they are needed.
+    ///
+    /// Each entry is a single value attribute, such as a [Float](AttributeType::Float) for a [FloatArray](AttributeType::FloatArray).
+    /// The iterator stops after the first error.
+    pub fn read_array_lazy<R: BufRead + Seek>(buffer: R, location: &ArrayLocation) -> Result<LazyArray<R>, BinarySerializationError> {
+        let mut buffer = buffer;
+        buffer.seek(SeekFrom::Start(location.position))?;
+
+        Ok(LazyArray {
+            reader: Reader::new(buffer),
+            remaining: location.len,
+            entry_type: location.entry_type,
+            entry_id: location.entry_id,
+        })
+    }
 
-                if attribute_name == "id" && attribute_value.get_type() == AttributeType::ObjectId {
-                    return Err(BinarySerializationError::InvalidIdAttribute {
-                        element: Element::clone(element),
-                    });
-                }
+    /// Encodes a root element like [Serializer::serialize_version] followed by a CRC32 checksum trailer.
+    ///
+    /// The trailer is the bytes `DCRC` and the little endian CRC32 of everything after the header line.
+    /// It comes after the last element, so readers that don't know about it still load the file.
+    /// Use [BinarySerializer::deserialize_with_checksum] to verify it.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, serializers::{BinarySerializationError, BinarySerializer}};
+    ///
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize_with_checksum(&mut buffer, &Header::default(), &Element::default(), BinarySerializer::version()).unwrap();
+    /// assert!(datamodel::deserialize_slice(&buffer).is_ok());
+    ///
+    /// let last = buffer.len() - 9;
+    /// buffer[last] ^= 1;
+    /// let mut data = buffer.as_slice();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let result = BinarySerializer::deserialize_with_checksum(&mut data, encoding, version);
+    /// assert!(matches!(result, Err(BinarySerializationError::ChecksumMismatch { .. })));
+    /// ```
+    pub fn serialize_with_checksum(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<(), BinarySerializationError> {
+        let mut data = Vec::new();
+        Self::serialize_version(&mut data, header, root, version)?;
+
+        let body_start = data.iter().position(|&byte| byte == b'\n').map_or(0, |position| position + 1);
+        let checksum = crc32(&data[body_start..]);
+
+        buffer.write_all(&data)?;
+        buffer.write_all(CHECKSUM_MAGIC)?;
+        buffer.write_all(&checksum.to_le_bytes())?;
+        Ok(())
+    }
 
-                if version >= VERSION_STRING_TABLE {
-                    writer.write_string_index(attribute_name.as_str(), version, &collected_strings)?;
-                } else {
-                    writer.write_string(attribute_name.as_str())?;
-                }
+    /// Encodes the root and every element it references with [BinaryOptions].
+    ///
+    /// # Example
+    /// Null entries are dropped from element arrays with [BinaryOptions::compact_element_arrays].
+    /// ```
+    /// use datamodel::{Element, Header, attribute::Attribute, serializers::{BinaryOptions, BinarySerializer}};
+    ///
+    /// let mut root = Element::default();
+    /// root.set_attribute("children", Attribute::nullable_element_array([None, Some(Element::default()), None]));
+    /// let options = BinaryOptions { compact_element_arrays: true, ..Default::default() };
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize_with_options(&mut buffer, &Header::default(), &root, 9, options).unwrap();
+    /// let (_, root) = datamodel::deserialize_slice(&buffer).unwrap();
+    /// assert_eq!(root.get_attribute("children").unwrap().as_element_array().unwrap().len(), 1);
+    /// ```
+    pub fn serialize_with_options(
+        buffer: &mut impl Write,
+        header: &Header,
+        root: &Element,
+        version: i32,
+        options: BinaryOptions,
+    ) -> Result<(), BinarySerializationError> {
+        write_elements(buffer, header, root, version, options, &mut |_| {}, None)
+    }
 
-                fn attribute_array_id(version: i32, attribute_id: i8) -> i8 {
-                    attribute_id
-                        + if version >= VERSION_UNSIGNED_INTEGERS {
-                            ATTRIBUTE_UNSIGNED_INTEGERS_ARRAY_OFFSET
-                        } else {
-                            ATTRIBUTE_INITIAL_ARRAY_OFFSET
-                        }
-                }
+    /// Encodes the root and every element it references so the output only depends on the content of the elements.
+    ///
+    /// The root is still the first element, the rest of the element table is ordered by id,
+    /// the string table is sorted, and the attributes of each element are written sorted by name.
+    /// Element array entries keep their order as it's part of the content.
+    ///
+    /// # Example
+    /// The same graph built with attributes inserted in a different order gives the same bytes.
+    /// ```
+    /// use datamodel::{Element, Header, attribute::{AttributeInfo, UUID}, serializers::BinarySerializer};
+    ///
+    /// let first = Element::full("DmElement", UUID::from_u128(1));
+    /// let second = Element::full("DmElement", UUID::from_u128(2));
+    /// let root_id = UUID::from_u128(3);
+    ///
+    /// let mut root = Element::full("DmElement", root_id);
+    /// root.set_attribute("first", Some(Element::clone(&first)).into_attribute());
+    /// root.set_attribute("second", Some(Element::clone(&second)).into_attribute());
+    /// let mut reordered = Element::full("DmElement", root_id);
+    /// reordered.set_attribute("second", Some(second).into_attribute());
+    /// reordered.set_attribute("first", Some(first).into_attribute());
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut reordered_buffer = Vec::new();
+    /// BinarySerializer::serialize_deterministic(&mut buffer, &Header::default(), &root, 9).unwrap();
+    /// BinarySerializer::serialize_deterministic(&mut reordered_buffer, &Header::default(), &reordered, 9).unwrap();
+    /// assert_eq!(buffer, reordered_buffer);
+    /// ```
+    pub fn serialize_deterministic(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<(), BinarySerializationError> {
+        let options = BinaryOptions {
+            deterministic: true,
+            ..Default::default()
+        };
+        write_elements(buffer, header, root, version, options, &mut |_| {}, None)
+    }
 
-                fn check_array_length(count: usize, attribute_name: &str, element: &Element) -> Result<(), BinarySerializationError> {
-                    if count > MAX_ARRAY_SIZE {
-                        return Err(BinarySerializationError::AttributeArrayTooLong {
-                            attribute: attribute_name.to_string(),
-                            element: Element::clone(element),
-                            count,
-                        });
-                    }
-                    Ok(())
-                }
+    /// Encodes the root and every element it references, reporting progress after each element is written.
+    ///
+    /// The total is the number of elements referenced by the root, so it's known before any element is written.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, attribute::AttributeInfo, serializers::BinarySerializer};
+    ///
+    /// let mut root = Element::default();
+    /// root.set_attribute("child", Some(Element::default()).into_attribute());
+    /// let mut updates = Vec::new();
+    /// BinarySerializer::serialize_with_progress(&mut Vec::new(), &Header::default(), &root, 9, &mut |update| updates.push((update.done, update.total))).unwrap();
+    /// assert_eq!(updates, [(1, 2), (2, 2)]);
+    /// ```
+    pub fn serialize_with_progress(
+        buffer: &mut impl Write,
+        header: &Header,
+        root: &Element,
+        version: i32,
+        progress: &mut impl FnMut(ProgressUpdate),
+    ) -> Result<(), BinarySerializationError> {
+        write_elements(buffer, header, root, version, BinaryOptions::default(), progress, None)
+    }
 
-                match &*attribute_value.get_inner() {
-                    AttributeValue::Element(value) => {
-                        writer.write_byte(ATTRIBUTE_ELEMENT_ID)?;
-                        let element_value = match value {
-                            Some(element_value) => element_value,
-                            None => {
-                                writer.write_integer(ELEMENT_INDEX_NULL)?;
-                                continue;
-                            }
-                        };
-                        writer.write_integer(collected_elements.get_index_of(element_value).unwrap() as i32)?;
-                    }
-                    AttributeValue::Integer(value) => {
-                        writer.write_byte(ATTRIBUTE_INTEGER_ID)?;
-                        writer.write_integer(*value)?;
-                    }
-                    AttributeValue::Float(value) => {
-                        writer.write_byte(ATTRIBUTE_FLOAT_ID)?;
-                        writer.write_float(*value)?;
-                    }
-                    AttributeValue::Boolean(value) => {
-                        writer.write_byte(ATTRIBUTE_BOOLEAN_ID)?;
-                        writer.write_unsigned_byte(*value as u8)?;
-                    }
-                    AttributeValue::String(value) => {
-                        writer.write_byte(ATTRIBUTE_STRING_ID)?;
-                        if version >= VERSION_GLOBAL_STRING_TABLE {
-                            writer.write_string_index(value, version, &collected_strings)?;
-                        } else {
-                            writer.write_string(value)?;
-                        }
-                    }
-                    AttributeValue::Binary(value) => {
-                        writer.write_byte(ATTRIBUTE_BINARY_ID)?;
-                        if value.0.len() > MAX_ARRAY_SIZE {
-                            return Err(BinarySerializationError::BinaryDataTooLong {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                count: value.0.len(),
-                            });
-                        }
-                        writer.write_integer(value.0.len() as i32)?;
-                        writer.write_unsigned_bytes(&value.0)?;
-                    }
-                    AttributeValue::ObjectId(value) => {
-                        if version >= VERSION_DEPRECATE_OBJECT_ID {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: 1,
-                                max: VERSION_STRING_TABLE,
-                            });
-                        }
-                        writer.write_byte(ATTRIBUTE_OBJECTID_ID)?;
-                        writer.write_uuid(*value)?;
-                    }
-                    AttributeValue::Time(value) => {
-                        if version < VERSION_DEPRECATE_OBJECT_ID {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: VERSION_DEPRECATE_OBJECT_ID,
-                                max: Self::version(),
-                            });
-                        }
-                        writer.write_byte(ATTRIBUTE_TIME_ID)?;
-                        writer.write_integer(value.0)?;
-                    }
-                    AttributeValue::Color(value) => {
-                        writer.write_byte(ATTRIBUTE_COLOR_ID)?;
-                        writer.write_integer(i32::from_le_bytes([value.red, value.green, value.blue, value.alpha]))?;
-                    }
-                    AttributeValue::Vector2(value) => {
-                        writer.write_byte(ATTRIBUTE_VECTOR2_ID)?;
-                        writer.write_float(value.x)?;
-                        writer.write_float(value.y)?;
-                    }
-                    AttributeValue::Vector3(value) => {
-                        writer.write_byte(ATTRIBUTE_VECTOR3_ID)?;
-                        writer.write_float(value.x)?;
-                        writer.write_float(value.y)?;
-                        writer.write_float(value.z)?;
-                    }
-                    AttributeValue::Vector4(value) => {
-                        writer.write_byte(ATTRIBUTE_VECTOR4_ID)?;
-                        writer.write_float(value.x)?;
-                        writer.write_float(value.y)?;
-                        writer.write_float(value.z)?;
-                        writer.write_float(value.w)?;
-                    }
-                    AttributeValue::Angle(value) => {
-                        writer.write_byte(ATTRIBUTE_ANGLE_ID)?;
-                        writer.write_float(value.pitch)?;
-                        writer.write_float(value.yaw)?;
-                        writer.write_float(value.roll)?;
-                    }
-                    AttributeValue::Quaternion(value) => {
-                        writer.write_byte(ATTRIBUTE_QUATERNION_ID)?;
-                        writer.write_float(value.x)?;
-                        writer.write_float(value.y)?;
-                        writer.write_float(value.z)?;
-                        writer.write_float(value.w)?;
-                    }
-                    AttributeValue::Matrix(value) => {
-                        writer.write_byte(ATTRIBUTE_MATRIX_ID)?;
-                        let bytes = value.0.iter().flatten().flat_map(|entry| entry.to_le_bytes()).collect::<Vec<u8>>();
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::ULong(value) => {
-                        if version < VERSION_UNSIGNED_INTEGERS {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: VERSION_UNSIGNED_INTEGERS,
-                                max: Self::version(),
-                            });
-                        }
-                        writer.write_byte(ATTRIBUTE_ULONG_ID)?;
-                        writer.write_unsigned_long(*value)?;
-                    }
-                    AttributeValue::UByte(value) => {
-                        if version < VERSION_UNSIGNED_INTEGERS {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: VERSION_UNSIGNED_INTEGERS,
-                                max: Self::version(),
-                            });
-                        }
-                        writer.write_byte(ATTRIBUTE_UBYTE_ID)?;
-                        writer.write_unsigned_byte(*value)?;
-                    }
-                    AttributeValue::ElementArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_ELEMENT_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let bytes = values
-                            .iter()
-                            .flat_map(|value| match value {
-                                Some(element) => (collected_elements.get_index_of(element).unwrap() as i32).to_le_bytes(),
-                                None => (-1i32).to_le_bytes(),
-                            })
-                            .collect::<Vec<u8>>();
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::IntegerArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_INTEGER_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::FloatArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_FLOAT_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::BooleanArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_BOOLEAN_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let bytes = values.iter().map(|value| *value as u8).collect::<Vec<u8>>();
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::StringArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_STRING_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        for value in values {
-                            writer.write_string(value)?;
-                        }
-                    }
-                    AttributeValue::BinaryArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_BINARY_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        for value in values {
-                            if value.0.len() > MAX_ARRAY_SIZE {
-                                return Err(BinarySerializationError::BinaryDataTooLong {
-                                    attribute: attribute_name.clone(),
-                                    element: Element::clone(element),
-                                    count: value.0.len(),
-                                });
-                            }
-                            writer.write_integer(value.0.len() as i32)?;
-                            writer.write_unsigned_bytes(&value.0)?;
-                        }
-                    }
-                    AttributeValue::ObjectIdArray(values) => {
-                        if version >= VERSION_DEPRECATE_OBJECT_ID {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: 1,
-                                max: VERSION_STRING_TABLE,
-                            });
-                        }
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_OBJECTID_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<UUID>());
-                        for value in values {
-                            bytes.extend(value.to_bytes_le());
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::TimeArray(values) => {
-                        if version < VERSION_DEPRECATE_OBJECT_ID {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: VERSION_DEPRECATE_OBJECT_ID,
-                                max: Self::version(),
-                            });
-                        }
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_TIME_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Time>());
-                        for value in values {
-                            bytes.extend(value.0.to_le_bytes());
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::ColorArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_COLOR_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Color>());
-                        for value in values {
-                            bytes.push(value.red);
-                            bytes.push(value.green);
-                            bytes.push(value.blue);
-                            bytes.push(value.alpha);
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::Vector2Array(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_VECTOR2_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Vector2>());
-                        for value in values {
-                            bytes.extend(value.x.to_le_bytes());
-                            bytes.extend(value.y.to_le_bytes());
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::Vector3Array(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_VECTOR3_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Vector3>());
-                        for value in values {
-                            bytes.extend(value.x.to_le_bytes());
-                            bytes.extend(value.y.to_le_bytes());
-                            bytes.extend(value.z.to_le_bytes());
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::Vector4Array(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_VECTOR4_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Vector4>());
-                        for value in values {
-                            bytes.extend(value.x.to_le_bytes());
-                            bytes.extend(value.y.to_le_bytes());
-                            bytes.extend(value.z.to_le_bytes());
-                            bytes.extend(value.w.to_le_bytes());
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::AngleArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_ANGLE_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Angle>());
-                        for value in values {
-                            bytes.extend(value.pitch.to_le_bytes());
-                            bytes.extend(value.yaw.to_le_bytes());
-                            bytes.extend(value.roll.to_le_bytes());
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::QuaternionArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_QUATERNION_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Quaternion>());
-                        for value in values {
-                            bytes.extend(value.x.to_le_bytes());
-                            bytes.extend(value.y.to_le_bytes());
-                            bytes.extend(value.z.to_le_bytes());
-                            bytes.extend(value.w.to_le_bytes());
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::MatrixArray(values) => {
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_MATRIX_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Matrix>());
-                        for value in values {
-                            for entry in value.0.iter().flatten() {
-                                bytes.extend(entry.to_le_bytes());
-                            }
-                        }
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::ULongArray(values) => {
-                        if version < VERSION_UNSIGNED_INTEGERS {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: VERSION_UNSIGNED_INTEGERS,
-                                max: Self::version(),
-                            });
-                        }
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_ULONG_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
-                        writer.write_unsigned_bytes(&bytes)?;
-                    }
-                    AttributeValue::UByteArray(values) => {
-                        if version < VERSION_UNSIGNED_INTEGERS {
-                            return Err(BinarySerializationError::InvalidVersionForAttribute {
-                                attribute: attribute_name.clone(),
-                                element: Element::clone(element),
-                                min: VERSION_UNSIGNED_INTEGERS,
-                                max: Self::version(),
-                            });
-                        }
-                        writer.write_byte(attribute_array_id(version, ATTRIBUTE_UBYTE_ID))?;
-                        check_array_length(values.len(), attribute_name, element)?;
-                        writer.write_integer(values.len() as i32)?;
-                        writer.write_unsigned_bytes(values)?;
-                    }
-                }
-            }
+    /// Encodes the root and every element it references, returning how many bytes each attribute type took.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, attribute::AttributeInfo, serializers::BinarySerializer};
+    ///
+    /// let mut root = Element::named("mesh");
+    /// root.set_attribute("positions", vec![0.0f32; 256].into_attribute());
+    /// root.set_attribute("count", 256.into_attribute());
+    ///
+    /// let mut buffer = Vec::new();
+    /// let report = BinarySerializer::serialize_with_report(&mut buffer, &Header::default(), &root, 9).unwrap();
+    /// assert_eq!(report.total_bytes, buffer.len());
+    /// assert_eq!(report.element_count, 1);
+    /// assert!(report.bytes_per_type["float_array"] > 256 * 4);
+    /// assert_eq!(report.bytes_per_type["int"], 4 + 1 + 4);
+    /// ```
+    pub fn serialize_with_report(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<SerializeReport, BinarySerializationError> {
+        let mut report = SerializeReport::default();
+        write_elements(buffer, header, root, version, BinaryOptions::default(), &mut |_| {}, Some(&mut report))?;
+        Ok(report)
+    }
+
+    /// Decodes the buffer for the root element after verifying the checksum trailer written by [BinarySerializer::serialize_with_checksum].
+    ///
+    /// The buffer must be positioned after the header line, the same as [Serializer::deserialize].
+    pub fn deserialize_with_checksum(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, BinarySerializationError> {
+        let mut data = Vec::new();
+        buffer.read_to_end(&mut data)?;
+
+        let trailer_start = data
+            .len()
+            .checked_sub(CHECKSUM_MAGIC.len() + 4)
+            .ok_or(BinarySerializationError::MissingChecksum)?;
+        let (body, trailer) = data.split_at(trailer_start);
+        let (magic, checksum) = trailer.split_at(CHECKSUM_MAGIC.len());
+        if magic != CHECKSUM_MAGIC {
+            return Err(BinarySerializationError::MissingChecksum);
         }
 
-        Ok(())
+        let expected = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        let found = crc32(body);
+        if expected != found {
+            return Err(BinarySerializationError::ChecksumMismatch { expected, found });
+        }
+
+        Self::deserialize(&mut &body[..], encoding, version)
     }
 
-    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
-        if !(1..=Self::version()).contains(&version) {
-            return Err(BinarySerializationError::InvalidVersion { version });
-        }
+    /// Decodes the buffer for the root element, rejecting files that declare more than `max_elements` elements.
+    ///
+    /// The length of the element table is checked as soon as it is read, before any element is created,
+    /// so loaders of untrusted files can bound the work done for a file.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::AttributeInfo, serializers::{BinarySerializationError, BinarySerializer}};
+    ///
+    /// let mut root = Element::default();
+    /// root.set_attribute("children", vec![Some(Element::default()), Some(Element::default())].into_attribute());
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+    ///
+    /// let mut data = buffer.as_slice();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let result = BinarySerializer::deserialize_with_max_elements(&mut data, encoding, version, 2);
+    /// assert!(matches!(result, Err(BinarySerializationError::InvalidElementTableLength { length: 3, max: 2, .. })));
+    /// ```
+    pub fn deserialize_with_max_elements(
+        buffer: &mut impl BufRead,
+        encoding: String,
+        version: i32,
+        max_elements: usize,
+    ) -> Result<Element, BinarySerializationError> {
+        check_encoding(&encoding, version)?;
+
+        let mut elements = Vec::new();
+        read_elements(buffer, version, max_elements, &mut elements, &mut Recovery::disabled(), &mut |_| None)?;
 
-        if encoding != Self::name() {
-            return Err(BinarySerializationError::WrongEncoding);
+        if elements.is_empty() {
+            return Err(BinarySerializationError::NoElements);
         }
 
-        let mut reader = Reader::new(buffer);
-        reader.read_string()?;
+        Ok(elements.swap_remove(0))
+    }
 
-        if version >= VERSION_PREFIX_ELEMENT && reader.read_integer()? != 0 {
-            let attribute_count = array_size_check(reader.read_integer()?)?;
-            for _ in 0..attribute_count {
-                reader.read_string()?;
-                let attribute_type = reader.read_byte()?;
-                if attribute_type == ATTRIBUTE_ELEMENT_ID
-                    || (version < VERSION_UNSIGNED_INTEGERS && attribute_type == ATTRIBUTE_ELEMENT_ID + ATTRIBUTE_INITIAL_ARRAY_OFFSET)
-                    || (version >= VERSION_UNSIGNED_INTEGERS && attribute_type == ATTRIBUTE_ELEMENT_ID + ATTRIBUTE_UNSIGNED_INTEGERS_ARRAY_OFFSET)
-                {
-                    return Err(BinarySerializationError::InvalidPrefixElementAttribute);
-                }
-                if attribute_type == ATTRIBUTE_STRING_ID {
-                    reader.read_string()?;
-                    continue;
-                }
-                reader.read_attribute(version, attribute_type)?;
-            }
+    /// Decodes the buffer for the root element, linking external element references with the resolver.
+    ///
+    /// External references store only the id of an element in another file.
+    /// The resolver is called with the id of each one and the element it returns is used in its place,
+    /// so elements already loaded from other files can be linked in one pass.
+    /// When it returns [None] the reference is an empty placeholder element with the id, the same as [BinarySerializer::deserialize](Serializer::deserialize).
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, UUID}, serializers::BinarySerializer};
+    ///
+    /// let mut root = Element::default();
+    /// root.set_attribute("model", None::<Element>.into_attribute());
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize_version(&mut buffer, &Header::default(), &root, 1).unwrap();
+    ///
+    /// // Replace the null reference at the end of the file with an external reference.
+    /// let model = Element::named("model");
+    /// buffer.truncate(buffer.len() - 4);
+    /// buffer.extend((-2i32).to_le_bytes());
+    /// buffer.extend(model.get_id().to_string().bytes().chain([0]));
+    ///
+    /// let mut data = buffer.as_slice();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let read = BinarySerializer::deserialize_with_resolver(&mut data, encoding, version, &mut |id: UUID| (id == *model.get_id()).then(|| model.clone())).unwrap();
+    /// let linked = read.get_attribute("model").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_eq!(linked.name_owned().as_deref(), Some("model"));
+    /// ```
+    pub fn deserialize_with_resolver(
+        buffer: &mut impl BufRead,
+        encoding: String,
+        version: i32,
+        resolver: &mut impl FnMut(UUID) -> Option<Element>,
+    ) -> Result<Element, BinarySerializationError> {
+        check_encoding(&encoding, version)?;
+
+        let mut elements = Vec::new();
+        read_elements(buffer, version, MAX_ARRAY_SIZE, &mut elements, &mut Recovery::disabled(), resolver)?;
+
+        if elements.is_empty() {
+            return Err(BinarySerializationError::NoElements);
         }
 
-        let string_table_size = if version >= VERSION_GLOBAL_STRING_TABLE {
-            array_size_check(reader.read_integer()?)?
-        } else if version >= VERSION_STRING_TABLE {
-            array_size_check(reader.read_short()? as i32)?
+        Ok(elements.swap_remove(0))
+    }
+
+    /// Decodes the buffer for the root element, keeping as much of the file as possible when it is damaged.
+    ///
+    /// Invalid string table and element table indices are recorded in [BinaryRecovery::skipped]:
+    /// the attribute with the bad name or string value is left out and a bad element reference becomes null.
+    /// Errors that make the rest of the file unreadable, such as an unknown attribute type whose size can't be known,
+    /// stop the load and are returned in [BinaryRecovery::error] with every element and attribute read before it.
+    ///
+    /// # Example
+    /// An element reference one past the end of the element table.
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, UUID}, serializers::{BinarySerializationError, BinarySerializer}};
+    ///
+    /// // Fixed ids, so the element table can't contain the bytes searched for below.
+    /// let mut root = Element::full("DmElement", UUID::from_u128(0xAAAA_AAAA_AAAA_AAAA_AAAA_AAAA_AAAA_AAAA));
+    /// root.set_attribute("child", Some(Element::full("DmElement", UUID::from_u128(0xBBBB_BBBB_BBBB_BBBB_BBBB_BBBB_BBBB_BBBB))).into_attribute());
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+    ///
+    /// // The element attribute type followed by index 1, the child, which becomes index 2 in a table of 2 elements.
+    /// let reference = buffer.windows(5).position(|bytes| bytes == [1, 1, 0, 0, 0]).unwrap();
+    /// buffer[reference + 1] = 2;
+    /// assert!(datamodel::deserialize_slice(&buffer).is_err());
+    ///
+    /// let mut data = buffer.as_slice();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let recovery = BinarySerializer::deserialize_recover(&mut data, encoding, version);
+    /// assert!(matches!(recovery.skipped[..], [BinarySerializationError::InvalidElementTableIndex { index: 2, size: 2, .. }]));
+    /// assert!(recovery.root.unwrap().get_attribute("child").unwrap().as_element().unwrap().is_none());
+    /// ```
+    ///
+    /// A file cut off in the middle of the last attribute keeps every element and the attributes before it.
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::AttributeInfo, serializers::{BinarySerializationError, BinarySerializer}};
+    ///
+    /// let mut root = Element::named("root");
+    /// root.set_attribute("count", 3.into_attribute());
+    /// let mut child = Element::named("child");
+    /// child.set_attribute("first", 1.into_attribute());
+    /// child.set_attribute("second", 2.into_attribute());
+    /// root.set_attribute("child", Some(child).into_attribute());
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+    /// buffer.truncate(buffer.len() - 2);
+    ///
+    /// let mut data = buffer.as_slice();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let recovery = BinarySerializer::deserialize_recover(&mut data, encoding, version);
+    /// assert!(matches!(recovery.error, Some(BinarySerializationError::BufferError(_))));
+    /// let root = recovery.root.unwrap();
+    /// assert_eq!(root.get_copied::<i32>("count"), Some(3));
+    /// let child = root.get_attribute("child").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_eq!(child.name_owned().as_deref(), Some("child"));
+    /// assert_eq!(child.get_copied::<i32>("first"), Some(1));
+    /// assert!(child.get_attribute("second").is_none());
+    /// ```
+    ///
+    /// An unknown attribute type stops the load at that attribute.
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, UUID}, serializers::{BinarySerializationError, BinarySerializer}};
+    ///
+    /// // Fixed ids, so the element table can't contain the bytes searched for below.
+    /// let mut root = Element::full("DmElement", UUID::from_u128(0xAAAA_AAAA_AAAA_AAAA_AAAA_AAAA_AAAA_AAAA));
+    /// let mut child = Element::full("DmElement", UUID::from_u128(0xBBBB_BBBB_BBBB_BBBB_BBBB_BBBB_BBBB_BBBB));
+    /// child.set_attribute("first", 1.into_attribute());
+    /// child.set_attribute("second", 2.into_attribute());
+    /// root.set_attribute("child", Some(child).into_attribute());
+    /// let mut buffer = Vec::new();
+    /// BinarySerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+    ///
+    /// // The integer attribute type followed by the value of "second".
+    /// let second = buffer.windows(5).rposition(|bytes| bytes == [2, 2, 0, 0, 0]).unwrap();
+    /// buffer[second] = 0x7F;
+    ///
+    /// let mut data = buffer.as_slice();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let recovery = BinarySerializer::deserialize_recover(&mut data, encoding, version);
+    /// assert!(matches!(recovery.error, Some(BinarySerializationError::UnknownAttribute { attribute_id: 0x7F, .. })));
+    /// let child = recovery.root.unwrap().get_attribute("child").unwrap().as_element().unwrap().clone().unwrap();
+    /// assert_eq!(child.get_copied::<i32>("first"), Some(1));
+    /// assert!(child.get_attribute("second").is_none());
+    /// ```
+    pub fn deserialize_recover(buffer: &mut impl BufRead, encoding: String, version: i32) -> BinaryRecovery {
+        let mut elements = Vec::new();
+        let mut recovery = Recovery::enabled();
+
+        let error = if encoding != Self::name() {
+            Some(BinarySerializationError::WrongEncoding)
+        } else if !(1..=Self::version()).contains(&version) {
+            Some(BinarySerializationError::InvalidVersion { version })
         } else {
-            0
+            read_elements(buffer, version, MAX_ARRAY_SIZE, &mut elements, &mut recovery, &mut |_| None).err()
         };
-        let mut string_table = Vec::with_capacity(string_table_size);
-        for _ in 0..string_table_size {
-            string_table.push(reader.read_string()?);
+
+        let error = match error {
+            None if elements.is_empty() => Some(BinarySerializationError::NoElements),
+            error => error,
+        };
+
+        BinaryRecovery {
+            root: if elements.is_empty() { None } else { Some(elements.swap_remove(0)) },
+            skipped: recovery.skipped,
+            error,
         }
+    }
+}
 
-        let element_size = array_size_check(reader.read_integer()?)?;
-        let mut elements = Vec::with_capacity(element_size);
-        for _ in 0..element_size {
-            let element_class = if version >= VERSION_LARGE_STRING_INDEX {
-                get_string_table_index(reader.read_integer()?, &string_table)?
-            } else if version >= VERSION_STRING_TABLE {
-                get_string_table_index(reader.read_short()? as i32, &string_table)?
-            } else {
-                reader.read_string()?
-            };
+/// Options that change how [BinarySerializer] writes a file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BinaryOptions {
+    /// Order the element table by id after the root, sort the string table, and write attributes sorted by name,
+    /// so the output only depends on the content of the elements. See [BinarySerializer::serialize_deterministic].
+    pub deterministic: bool,
+    /// Leave null entries out of element arrays.
+    ///
+    /// This is lossy, the arrays get shorter and the entries after a null move to lower indices.
+    pub compact_element_arrays: bool,
+    /// If strings are written to a string table or inline.
+    pub string_table: StringTableMode,
+}
 
-            if (VERSION_LINK_TYPE..VERSION_DEPRECATE_LINK_TYPE).contains(&version) {
-                reader.read_integer()?;
-            }
+/// How [BinarySerializer] writes strings, set with [BinaryOptions::string_table].
+///
+/// Only version 1 writes every string inline, so choosing inline strings writes a version 1 file and the header has version 1.
+/// Version 1 can't store every attribute type, see [Serializer::supports].
+///
+/// # Example
+/// A small file with few repeated strings is smaller without the string table.
+/// ```
+/// use datamodel::{Element, Header, serializers::{BinaryOptions, BinarySerializer, StringTableMode}};
+///
+/// let root = Element::named("root");
+/// let mut table = Vec::new();
+/// BinarySerializer::serialize_with_options(&mut table, &Header::default(), &root, 9, BinaryOptions::default()).unwrap();
+/// let options = BinaryOptions { string_table: StringTableMode::Smallest, ..Default::default() };
+/// let mut smallest = Vec::new();
+/// BinarySerializer::serialize_with_options(&mut smallest, &Header::default(), &root, 9, options).unwrap();
+/// assert!(smallest.len() < table.len());
+///
+/// let (_, encoding, version) = Header::from_buffer(&mut smallest.as_slice()).unwrap();
+/// assert_eq!((encoding.as_str(), version), ("binary", 1));
+/// let (_, read) = datamodel::deserialize_slice(&smallest).unwrap();
+/// assert_eq!(read.name_owned().as_deref(), Some("root"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringTableMode {
+    /// Write the string table of the selected version.
+    #[default]
+    Always,
+    /// Write every string inline as version 1.
+    ///
+    /// Returns [BinarySerializationError::InvalidVersionForAttribute] if an attribute can't be stored in version 1.
+    Never,
+    /// Write version 1 when every attribute can be stored in it and the strings are estimated to be smaller inline, otherwise the selected version.
+    Smallest,
+}
 
-            let element_name = if version >= VERSION_LARGE_STRING_INDEX {
-                get_string_table_index(reader.read_integer()?, &string_table)?
-            } else if version >= VERSION_GLOBAL_STRING_TABLE {
-                get_string_table_index(reader.read_short()? as i32, &string_table)?
-            } else {
-                reader.read_string()?
-            };
+/// How far [BinarySerializer::serialize_with_progress] is through writing the elements.
+#[derive(Clone, Copy, Debug)]
+pub struct ProgressUpdate {
+    /// The number of elements written so far.
+    pub done: usize,
+    /// The number of elements that will be written.
+    pub total: usize,
+}
 
-            let element_id = reader.read_uuid()?;
-            let mut new_element = Element::full(element_class, element_id);
-            new_element.set_attribute("name", element_name.into_attribute());
-            elements.push(new_element);
-        }
+/// The sizes written by [BinarySerializer::serialize_with_report].
+#[derive(Debug, Clone, Default)]
+pub struct SerializeReport {
+    /// The bytes written for the attributes of each [type name](AttributeType::type_name), including the attribute names and type bytes.
+    pub bytes_per_type: HashMap<&'static str, usize>,
+    /// The number of elements in the element table.
+    pub element_count: usize,
+    /// The number of strings in the string table, 0 for version 1 which has no string table.
+    pub symbol_count: usize,
+    /// The bytes written for the whole file, including the header and tables that aren't counted in [SerializeReport::bytes_per_type].
+    pub total_bytes: usize,
+}
 
-        for element_index in 0..element_size {
-            let attribute_count = array_size_check(reader.read_integer()?)?;
-            let mut current_element = Element::clone(&elements[element_index]);
-            for _ in 0..attribute_count {
-                let attribute_name = if version >= VERSION_LARGE_STRING_INDEX {
-                    get_string_table_index(reader.read_integer()?, &string_table)?
-                } else if version >= VERSION_STRING_TABLE {
-                    get_string_table_index(reader.read_short()? as i32, &string_table)?
-                } else {
-                    reader.read_string()?
-                };
-                let attribute_type = reader.read_byte()?;
-                let attribute_value = if attribute_type == ATTRIBUTE_ELEMENT_ID {
-                    (match reader.read_integer()? {
-                        index if index < ELEMENT_INDEX_EXTERNAL || index > element_size as i32 => {
-                            return Err(BinarySerializationError::InvalidElementTableIndex { index, size: element_size });
-                        }
-                        ELEMENT_INDEX_NULL => None,
-                        ELEMENT_INDEX_EXTERNAL => Some(Element::full(Element::class_name(), UUID::from_str(&reader.read_string()?)?)),
-                        index => Some(Element::clone(&elements[index as usize])),
-                    })
-                    .into_attribute()
-                } else if (version < VERSION_UNSIGNED_INTEGERS && attribute_type == ATTRIBUTE_ELEMENT_ID + ATTRIBUTE_INITIAL_ARRAY_OFFSET)
-                    || (version >= VERSION_UNSIGNED_INTEGERS && attribute_type == ATTRIBUTE_ELEMENT_ID + ATTRIBUTE_UNSIGNED_INTEGERS_ARRAY_OFFSET)
-                {
-                    let array_size = array_size_check(reader.read_integer()?)?;
-                    let mut attribute_array = Vec::with_capacity(array_size);
-                    for _ in 0..array_size {
-                        attribute_array.push(match reader.read_integer()? {
-                            index if index < ELEMENT_INDEX_EXTERNAL || index > element_size as i32 => {
-                                return Err(BinarySerializationError::InvalidElementTableIndex { index, size: element_size });
-                            }
-                            ELEMENT_INDEX_NULL => None,
-                            ELEMENT_INDEX_EXTERNAL => Some(Element::full(Element::class_name(), UUID::from_str(&reader.read_string()?)?)),
-                            index => Some(Element::clone(&elements[index as usize])),
-                        });
-                    }
-                    attribute_array.into_attribute()
-                } else if attribute_type == ATTRIBUTE_STRING_ID {
-                    (if version >= VERSION_LARGE_STRING_INDEX {
-                        get_string_table_index(reader.read_integer()?, &string_table)?
-                    } else if version >= VERSION_GLOBAL_STRING_TABLE {
-                        get_string_table_index(reader.read_short()? as i32, &string_table)?
-                    } else {
-                        reader.read_string()?
-                    })
-                    .into_attribute()
-                } else {
-                    reader.read_attribute(version, attribute_type)?
-                };
-                current_element.set_attribute(attribute_name, attribute_value);
-            }
+/// Where an array attribute is in a buffer, from [BinarySerializer::locate_array].
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayLocation {
+    /// The position of the first entry from the start of the buffer.
+    pub position: u64,
+    /// The number of entries in the array.
+    pub len: usize,
+    /// The type of the array attribute.
+    pub attribute_type: AttributeType,
+    entry_type: AttributeType,
+    entry_id: i8,
+}
+
+/// An iterator that reads the entries of an array attribute from a buffer one at a time, from [BinarySerializer::read_array_lazy].
+pub struct LazyArray<R: BufRead> {
+    reader: Reader<R>,
+    remaining: usize,
+    entry_type: AttributeType,
+    entry_id: i8,
+}
+
+impl<R: BufRead> Iterator for LazyArray<R> {
+    type Item = Result<Attribute, BinarySerializationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
         }
+        self.remaining -= 1;
 
-        if elements.is_empty() {
-            return Err(BinarySerializationError::NoElements);
+        let entry = match self.entry_type {
+            AttributeType::String => self.reader.read_string().map(String::into_attribute),
+            entry_type => self.reader.read_single_attribute(entry_type, self.entry_id, self.reader.offset),
+        };
+        if entry.is_err() {
+            self.remaining = 0;
         }
+        Some(entry)
+    }
 
-        Ok(elements.remove(0))
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
     }
 }
 
+/// The outcome of [BinarySerializer::deserialize_recover].
+#[derive(Debug)]
+pub struct BinaryRecovery {
+    /// The root element with everything read before the load stopped, or [None] if the element table couldn't be read.
+    pub root: Option<Element>,
+    /// Errors that were recovered from, in the order they were found.
+    pub skipped: Vec<BinarySerializationError>,
+    /// The error that stopped the load, if any.
+    pub error: Option<BinarySerializationError>,
+}
+
 const VERSION_STRING_TABLE: i32 = 2;
 const VERSION_DEPRECATE_OBJECT_ID: i32 = 3;
 const VERSION_GLOBAL_STRING_TABLE: i32 = 4;
@@ -691,99 +857,584 @@ const VERSION_UNSIGNED_INTEGERS: i32 = 9;
 const MAX_SHORT_ARRAY_SIZE: usize = (i16::MAX as usize) + 1;
 const MAX_ARRAY_SIZE: usize = (i32::MAX as usize) + 1;
 
-const ATTRIBUTE_ELEMENT_ID: i8 = 1;
-const ATTRIBUTE_INTEGER_ID: i8 = 2;
-const ATTRIBUTE_FLOAT_ID: i8 = 3;
-const ATTRIBUTE_BOOLEAN_ID: i8 = 4;
-const ATTRIBUTE_STRING_ID: i8 = 5;
-const ATTRIBUTE_BINARY_ID: i8 = 6;
-const ATTRIBUTE_OBJECTID_ID: i8 = 7;
-const ATTRIBUTE_TIME_ID: i8 = 7;
-const ATTRIBUTE_COLOR_ID: i8 = 8;
-const ATTRIBUTE_VECTOR2_ID: i8 = 9;
-const ATTRIBUTE_VECTOR3_ID: i8 = 10;
-const ATTRIBUTE_VECTOR4_ID: i8 = 11;
-const ATTRIBUTE_ANGLE_ID: i8 = 12;
-const ATTRIBUTE_QUATERNION_ID: i8 = 13;
-const ATTRIBUTE_MATRIX_ID: i8 = 14;
-const ATTRIBUTE_ULONG_ID: i8 = 15;
-const ATTRIBUTE_UBYTE_ID: i8 = 16;
+/// The type byte of each attribute type when it isn't an array.
+///
+/// ObjectId and Time share a byte, ObjectId is only in versions before 3 and Time only in 3 and after.
+const ATTRIBUTE_TYPE_IDS: [(AttributeType, AttributeType, i8); 17] = [
+    (AttributeType::Element, AttributeType::ElementArray, 1),
+    (AttributeType::Integer, AttributeType::IntegerArray, 2),
+    (AttributeType::Float, AttributeType::FloatArray, 3),
+    (AttributeType::Boolean, AttributeType::BooleanArray, 4),
+    (AttributeType::String, AttributeType::StringArray, 5),
+    (AttributeType::Binary, AttributeType::BinaryArray, 6),
+    (AttributeType::ObjectId, AttributeType::ObjectIdArray, 7),
+    (AttributeType::Time, AttributeType::TimeArray, 7),
+    (AttributeType::Color, AttributeType::ColorArray, 8),
+    (AttributeType::Vector2, AttributeType::Vector2Array, 9),
+    (AttributeType::Vector3, AttributeType::Vector3Array, 10),
+    (AttributeType::Vector4, AttributeType::Vector4Array, 11),
+    (AttributeType::Angle, AttributeType::AngleArray, 12),
+    (AttributeType::Quaternion, AttributeType::QuaternionArray, 13),
+    (AttributeType::Matrix, AttributeType::MatrixArray, 14),
+    (AttributeType::ULong, AttributeType::ULongArray, 15),
+    (AttributeType::UByte, AttributeType::UByteArray, 16),
+];
 
 const ATTRIBUTE_INITIAL_ARRAY_OFFSET: i8 = 14;
 const ATTRIBUTE_UNSIGNED_INTEGERS_ARRAY_OFFSET: i8 = 32;
 
+/// The amount added to a type byte to make it the array type byte.
+fn attribute_array_offset(version: i32) -> i8 {
+    if version >= VERSION_UNSIGNED_INTEGERS {
+        ATTRIBUTE_UNSIGNED_INTEGERS_ARRAY_OFFSET
+    } else {
+        ATTRIBUTE_INITIAL_ARRAY_OFFSET
+    }
+}
+
+/// The type byte of an attribute type in a version, or [None] if the version can't store it.
+fn attribute_type_id(attribute_type: AttributeType, version: i32) -> Option<i8> {
+    if !BinarySerializer::supports(attribute_type, version) {
+        return None;
+    }
+
+    ATTRIBUTE_TYPE_IDS.iter().find_map(|&(single_type, array_type, type_id)| {
+        if attribute_type == single_type {
+            Some(type_id)
+        } else if attribute_type == array_type {
+            Some(type_id + attribute_array_offset(version))
+        } else {
+            None
+        }
+    })
+}
+
+/// The attribute type of a type byte in a version and if it's the array type, or [None] if the byte is unknown.
+fn attribute_type_from_id(attribute_id: i8, version: i32) -> Option<(AttributeType, bool)> {
+    let array_offset = attribute_array_offset(version);
+    let (type_id, is_array) = if attribute_id > array_offset {
+        (attribute_id - array_offset, true)
+    } else {
+        (attribute_id, false)
+    };
+
+    ATTRIBUTE_TYPE_IDS
+        .iter()
+        .filter(|&&(single_type, _, _)| BinarySerializer::supports(single_type, version))
+        .find(|&&(_, _, single_id)| single_id == type_id)
+        .map(|&(single_type, array_type, _)| if is_array { (array_type, true) } else { (single_type, false) })
+}
+
 const ELEMENT_INDEX_NULL: i32 = -1;
 const ELEMENT_INDEX_EXTERNAL: i32 = -2;
 
 struct Writer<T: Write> {
     buffer: T,
+    written: usize,
 }
 
 impl<T: Write> Writer<T> {
     fn new(buffer: T) -> Self {
-        Self { buffer }
+        Self { buffer, written: 0 }
     }
 
-    fn write_string(&mut self, value: &str) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(value.as_bytes())?;
-        self.buffer.write_all(&[0])?;
-        Ok(())
-    }
+    fn write_bytes(&mut self, value: &[u8]) -> Result<(), BinarySerializationError> {
+        self.buffer.write_all(value)?;
+        self.written += value.len();
+        Ok(())
+    }
+
+    fn write_string(&mut self, value: &str) -> Result<(), BinarySerializationError> {
+        self.write_bytes(value.as_bytes())?;
+        self.write_bytes(&[0])?;
+        Ok(())
+    }
+
+    fn write_string_index(&mut self, value: &str, version: i32, collected_strings: &IndexSet<String>) -> Result<(), BinarySerializationError> {
+        if version >= VERSION_LARGE_STRING_INDEX {
+            if value.is_empty() {
+                return self.write_integer(-1);
+            }
+
+            return self.write_integer(collected_strings.get_index_of(value).unwrap() as i32);
+        }
+
+        if value.is_empty() {
+            return self.write_short(-1);
+        }
+        self.write_short(collected_strings.get_index_of(value).unwrap() as i16)
+    }
+
+    fn write_byte(&mut self, value: i8) -> Result<(), BinarySerializationError> {
+        self.write_bytes(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn write_unsigned_byte(&mut self, value: u8) -> Result<(), BinarySerializationError> {
+        self.write_bytes(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn write_unsigned_bytes(&mut self, value: &[u8]) -> Result<(), BinarySerializationError> {
+        self.write_bytes(value)?;
+        Ok(())
+    }
+
+    fn write_short(&mut self, value: i16) -> Result<(), BinarySerializationError> {
+        self.write_bytes(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn write_integer(&mut self, value: i32) -> Result<(), BinarySerializationError> {
+        self.write_bytes(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn write_unsigned_long(&mut self, value: u64) -> Result<(), BinarySerializationError> {
+        self.write_bytes(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn write_float(&mut self, value: f32) -> Result<(), BinarySerializationError> {
+        self.write_bytes(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn write_uuid(&mut self, value: UUID) -> Result<(), BinarySerializationError> {
+        self.write_bytes(&value.to_bytes_le())?;
+        Ok(())
+    }
+}
+
+/// Writes the root and every element it references, calling the progress callback after each element's attributes are written.
+///
+/// The sizes written are recorded in the report if there is one.
+fn write_elements(
+    buffer: &mut impl Write,
+    header: &Header,
+    root: &Element,
+    version: i32,
+    options: BinaryOptions,
+    progress: &mut impl FnMut(ProgressUpdate),
+    mut report: Option<&mut SerializeReport>,
+) -> Result<(), BinarySerializationError> {
+    if !(1..=BinarySerializer::version()).contains(&version) {
+        return Err(BinarySerializationError::InvalidVersion { version });
+    }
+
+    let mut collected_elements = collect_elements(root);
+    if options.deterministic {
+        collected_elements.sort_by(|left, right| (left != root).cmp(&(right != root)).then_with(|| left.get_id().cmp(&right.get_id())));
+    }
+
+    let version = match options.string_table {
+        StringTableMode::Always => version,
+        StringTableMode::Never => 1,
+        StringTableMode::Smallest => {
+            if version > 1
+                && check_attribute_versions(&collected_elements, 1).is_ok()
+                && estimate_string_size(&collected_elements, 1) < estimate_string_size(&collected_elements, version)
+            {
+                1
+            } else {
+                version
+            }
+        }
+    };
+    check_attribute_versions(&collected_elements, version)?;
+
+    let mut writer = Writer::new(buffer);
+    writer.write_string(&header.create_header(BinarySerializer::name(), version))?;
+
+    if version >= VERSION_PREFIX_ELEMENT {
+        writer.write_integer(0)?;
+    }
+
+    let mut collected_strings = collect_strings(&collected_elements, version);
+    if options.deterministic {
+        collected_strings.sort();
+    }
+
+    let max_string_table_length = if version >= VERSION_GLOBAL_STRING_TABLE {
+        MAX_ARRAY_SIZE
+    } else {
+        MAX_SHORT_ARRAY_SIZE
+    };
+    if collected_strings.len() > max_string_table_length {
+        return Err(BinarySerializationError::TooManyStrings {
+            count: collected_strings.len(),
+            max: max_string_table_length,
+        });
+    }
+    if version >= VERSION_STRING_TABLE {
+        if version >= VERSION_GLOBAL_STRING_TABLE {
+            writer.write_integer(collected_strings.len() as i32)?;
+        } else {
+            writer.write_short(collected_strings.len() as i16)?;
+        }
+
+        for string in &collected_strings {
+            writer.write_string(string)?;
+        }
+    }
+
+    if collected_elements.len() > MAX_ARRAY_SIZE {
+        return Err(BinarySerializationError::TooManyElements {
+            count: collected_elements.len(),
+        });
+    }
+    writer.write_integer(collected_elements.len() as i32)?;
+    if let Some(report) = report.as_deref_mut() {
+        report.element_count = collected_elements.len();
+        report.symbol_count = collected_strings.len();
+    }
+    for element in &collected_elements {
+        if version >= VERSION_STRING_TABLE {
+            writer.write_string_index(&element.get_class(), version, &collected_strings)?;
+        } else {
+            writer.write_string(&element.get_class())?;
+        }
+
+        if (VERSION_LINK_TYPE..VERSION_DEPRECATE_LINK_TYPE).contains(&version) {
+            writer.write_integer(-1)?;
+        }
 
-    fn write_string_index(&mut self, value: &str, version: i32, collected_strings: &IndexSet<String>) -> Result<(), BinarySerializationError> {
-        if version >= VERSION_LARGE_STRING_INDEX {
-            if value.is_empty() {
-                return self.write_integer(-1);
+        if let Some(element_name_attribute) = element.get_attribute("name") {
+            if let AttributeValue::String(element_name) = &*element_name_attribute.get_inner() {
+                if version >= VERSION_GLOBAL_STRING_TABLE {
+                    writer.write_string_index(element_name, version, &collected_strings)?;
+                } else {
+                    writer.write_string(element_name)?;
+                }
+            } else {
+                return Err(BinarySerializationError::InvalidNameAttribute {
+                    element: Element::clone(element),
+                });
             }
+        } else if version >= VERSION_GLOBAL_STRING_TABLE {
+            writer.write_string_index("", version, &collected_strings)?;
+        } else {
+            writer.write_string("")?;
+        }
 
-            return self.write_integer(collected_strings.get_index_of(value).unwrap() as i32);
+        writer.write_uuid(*element.get_id())?;
+    }
+
+    for (element_index, element) in collected_elements.iter().enumerate() {
+        let element_attributes = element.get_attributes();
+        let attribute_count = element_attributes.len() - element_attributes.contains_key("name") as usize;
+        if attribute_count > MAX_ARRAY_SIZE {
+            return Err(BinarySerializationError::TooManyAttributes {
+                element: Element::clone(element),
+                count: attribute_count,
+            });
         }
+        writer.write_integer(attribute_count as i32)?;
 
-        if value.is_empty() {
-            return self.write_short(-1);
+        let mut ordered_attributes = element_attributes.iter().collect::<Vec<_>>();
+        if options.deterministic {
+            ordered_attributes.sort_by_key(|(attribute_name, _)| *attribute_name);
         }
-        self.write_short(collected_strings.get_index_of(value).unwrap() as i16)
-    }
 
-    fn write_byte(&mut self, value: i8) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(&value.to_le_bytes())?;
-        Ok(())
-    }
+        for (attribute_name, attribute_value) in ordered_attributes {
+            if attribute_name == "name" {
+                continue;
+            }
 
-    fn write_unsigned_byte(&mut self, value: u8) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(&value.to_le_bytes())?;
-        Ok(())
-    }
+            if attribute_name == "id" && attribute_value.get_type() == AttributeType::ObjectId {
+                return Err(BinarySerializationError::InvalidIdAttribute {
+                    element: Element::clone(element),
+                });
+            }
 
-    fn write_unsigned_bytes(&mut self, value: &[u8]) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(value)?;
-        Ok(())
-    }
+            let attribute_start = writer.written;
+            if version >= VERSION_STRING_TABLE {
+                writer.write_string_index(attribute_name.as_str(), version, &collected_strings)?;
+            } else {
+                writer.write_string(attribute_name.as_str())?;
+            }
 
-    fn write_short(&mut self, value: i16) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(&value.to_le_bytes())?;
-        Ok(())
-    }
+            fn check_array_length(count: usize, attribute_name: &str, element: &Element) -> Result<(), BinarySerializationError> {
+                if count > MAX_ARRAY_SIZE {
+                    return Err(BinarySerializationError::AttributeArrayTooLong {
+                        attribute: attribute_name.to_string(),
+                        element: Element::clone(element),
+                        count,
+                    });
+                }
+                Ok(())
+            }
 
-    fn write_integer(&mut self, value: i32) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(&value.to_le_bytes())?;
-        Ok(())
-    }
+            let attribute_id = attribute_type_id(attribute_value.get_type(), version).ok_or_else(|| {
+                let (min, max) = attribute_version_range(attribute_value.get_type());
+                BinarySerializationError::InvalidVersionForAttribute {
+                    attribute: attribute_name.clone(),
+                    element: Element::clone(element),
+                    min,
+                    max,
+                }
+            })?;
+            writer.write_byte(attribute_id)?;
+
+            match &*attribute_value.get_inner() {
+                AttributeValue::Element(value) => match value {
+                    Some(element_value) => writer.write_integer(collected_elements.get_index_of(element_value).unwrap() as i32)?,
+                    None => writer.write_integer(ELEMENT_INDEX_NULL)?,
+                },
+                AttributeValue::Integer(value) => {
+                    writer.write_integer(*value)?;
+                }
+                AttributeValue::Float(value) => {
+                    writer.write_float(*value)?;
+                }
+                AttributeValue::Boolean(value) => {
+                    writer.write_unsigned_byte(*value as u8)?;
+                }
+                AttributeValue::String(value) => {
+                    if version >= VERSION_GLOBAL_STRING_TABLE {
+                        writer.write_string_index(value, version, &collected_strings)?;
+                    } else {
+                        writer.write_string(value)?;
+                    }
+                }
+                AttributeValue::Binary(value) => {
+                    if value.0.len() > MAX_ARRAY_SIZE {
+                        return Err(BinarySerializationError::BinaryDataTooLong {
+                            attribute: attribute_name.clone(),
+                            element: Element::clone(element),
+                            count: value.0.len(),
+                        });
+                    }
+                    writer.write_integer(value.0.len() as i32)?;
+                    writer.write_unsigned_bytes(&value.0)?;
+                }
+                AttributeValue::ObjectId(value) => {
+                    writer.write_uuid(*value)?;
+                }
+                AttributeValue::Time(value) => {
+                    writer.write_integer(value.0)?;
+                }
+                AttributeValue::Color(value) => {
+                    writer.write_integer(i32::from_le_bytes([value.red, value.green, value.blue, value.alpha]))?;
+                }
+                AttributeValue::Vector2(value) => {
+                    writer.write_float(value.x)?;
+                    writer.write_float(value.y)?;
+                }
+                AttributeValue::Vector3(value) => {
+                    writer.write_float(value.x)?;
+                    writer.write_float(value.y)?;
+                    writer.write_float(value.z)?;
+                }
+                AttributeValue::Vector4(value) => {
+                    writer.write_float(value.x)?;
+                    writer.write_float(value.y)?;
+                    writer.write_float(value.z)?;
+                    writer.write_float(value.w)?;
+                }
+                AttributeValue::Angle(value) => {
+                    writer.write_float(value.pitch)?;
+                    writer.write_float(value.yaw)?;
+                    writer.write_float(value.roll)?;
+                }
+                AttributeValue::Quaternion(value) => {
+                    writer.write_float(value.x)?;
+                    writer.write_float(value.y)?;
+                    writer.write_float(value.z)?;
+                    writer.write_float(value.w)?;
+                }
+                AttributeValue::Matrix(value) => {
+                    let bytes = value.0.iter().flatten().flat_map(|entry| entry.to_le_bytes()).collect::<Vec<u8>>();
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::ULong(value) => {
+                    writer.write_unsigned_long(*value)?;
+                }
+                AttributeValue::UByte(value) => {
+                    writer.write_unsigned_byte(*value)?;
+                }
+                AttributeValue::ElementArray(values) => {
+                    let values = values
+                        .iter()
+                        .filter(|value| !options.compact_element_arrays || value.is_some())
+                        .collect::<Vec<_>>();
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let bytes = values
+                        .into_iter()
+                        .flat_map(|value| match value {
+                            Some(element) => (collected_elements.get_index_of(element).unwrap() as i32).to_le_bytes(),
+                            None => (-1i32).to_le_bytes(),
+                        })
+                        .collect::<Vec<u8>>();
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::IntegerArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::FloatArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::BooleanArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let bytes = values.iter().map(|value| *value as u8).collect::<Vec<u8>>();
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::StringArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    for value in values {
+                        writer.write_string(value)?;
+                    }
+                }
+                AttributeValue::BinaryArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    for value in values {
+                        if value.0.len() > MAX_ARRAY_SIZE {
+                            return Err(BinarySerializationError::BinaryDataTooLong {
+                                attribute: attribute_name.clone(),
+                                element: Element::clone(element),
+                                count: value.0.len(),
+                            });
+                        }
+                        writer.write_integer(value.0.len() as i32)?;
+                        writer.write_unsigned_bytes(&value.0)?;
+                    }
+                }
+                AttributeValue::ObjectIdArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<UUID>());
+                    for value in values {
+                        bytes.extend(value.to_bytes_le());
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::TimeArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Time>());
+                    for value in values {
+                        bytes.extend(value.0.to_le_bytes());
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::ColorArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Color>());
+                    for value in values {
+                        bytes.push(value.red);
+                        bytes.push(value.green);
+                        bytes.push(value.blue);
+                        bytes.push(value.alpha);
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::Vector2Array(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Vector2>());
+                    for value in values {
+                        bytes.extend(value.x.to_le_bytes());
+                        bytes.extend(value.y.to_le_bytes());
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::Vector3Array(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Vector3>());
+                    for value in values {
+                        bytes.extend(value.x.to_le_bytes());
+                        bytes.extend(value.y.to_le_bytes());
+                        bytes.extend(value.z.to_le_bytes());
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::Vector4Array(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Vector4>());
+                    for value in values {
+                        bytes.extend(value.x.to_le_bytes());
+                        bytes.extend(value.y.to_le_bytes());
+                        bytes.extend(value.z.to_le_bytes());
+                        bytes.extend(value.w.to_le_bytes());
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::AngleArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Angle>());
+                    for value in values {
+                        bytes.extend(value.pitch.to_le_bytes());
+                        bytes.extend(value.yaw.to_le_bytes());
+                        bytes.extend(value.roll.to_le_bytes());
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::QuaternionArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Quaternion>());
+                    for value in values {
+                        bytes.extend(value.x.to_le_bytes());
+                        bytes.extend(value.y.to_le_bytes());
+                        bytes.extend(value.z.to_le_bytes());
+                        bytes.extend(value.w.to_le_bytes());
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::MatrixArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let mut bytes: Vec<u8> = Vec::with_capacity(values.len() * size_of::<Matrix>());
+                    for value in values {
+                        for entry in value.0.iter().flatten() {
+                            bytes.extend(entry.to_le_bytes());
+                        }
+                    }
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::ULongArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
+                    writer.write_unsigned_bytes(&bytes)?;
+                }
+                AttributeValue::UByteArray(values) => {
+                    check_array_length(values.len(), attribute_name, element)?;
+                    writer.write_integer(values.len() as i32)?;
+                    writer.write_unsigned_bytes(values)?;
+                }
+            }
 
-    fn write_unsigned_long(&mut self, value: u64) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(&value.to_le_bytes())?;
-        Ok(())
-    }
+            if let Some(report) = report.as_deref_mut() {
+                *report.bytes_per_type.entry(attribute_value.get_type().type_name()).or_default() += writer.written - attribute_start;
+            }
+        }
 
-    fn write_float(&mut self, value: f32) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(&value.to_le_bytes())?;
-        Ok(())
+        progress(ProgressUpdate {
+            done: element_index + 1,
+            total: collected_elements.len(),
+        });
     }
 
-    fn write_uuid(&mut self, value: UUID) -> Result<(), BinarySerializationError> {
-        self.buffer.write_all(&value.to_bytes_le())?;
-        Ok(())
+    if let Some(report) = report {
+        report.total_bytes = writer.written;
     }
+
+    Ok(())
 }
 
 fn collect_elements(root: &Element) -> IndexSet<Element> {
@@ -817,6 +1468,37 @@ fn collect_elements(root: &Element) -> IndexSet<Element> {
     collected_elements
 }
 
+/// Returns the range of versions that can store the attribute type.
+fn attribute_version_range(attribute_type: AttributeType) -> (i32, i32) {
+    match attribute_type {
+        AttributeType::ObjectId | AttributeType::ObjectIdArray => (1, VERSION_DEPRECATE_OBJECT_ID - 1),
+        AttributeType::Time | AttributeType::TimeArray => (VERSION_DEPRECATE_OBJECT_ID, BinarySerializer::version()),
+        AttributeType::ULong | AttributeType::ULongArray | AttributeType::UByte | AttributeType::UByteArray => {
+            (VERSION_UNSIGNED_INTEGERS, BinarySerializer::version())
+        }
+        _ => (1, BinarySerializer::version()),
+    }
+}
+
+/// Checks every attribute can be stored in the version before anything is written.
+fn check_attribute_versions(collected_elements: &IndexSet<Element>, version: i32) -> Result<(), BinarySerializationError> {
+    for element in collected_elements {
+        for (attribute_name, attribute_value) in element.get_attributes().iter() {
+            if !BinarySerializer::supports(attribute_value.get_type(), version) {
+                let (min, max) = attribute_version_range(attribute_value.get_type());
+                return Err(BinarySerializationError::InvalidVersionForAttribute {
+                    attribute: attribute_name.clone(),
+                    element: Element::clone(element),
+                    min,
+                    max,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn collect_strings(collected_elements: &IndexSet<Element>, version: i32) -> IndexSet<String> {
     if version < VERSION_STRING_TABLE {
         return IndexSet::new();
@@ -824,7 +1506,7 @@ fn collect_strings(collected_elements: &IndexSet<Element>, version: i32) -> Inde
 
     let mut collected_strings = IndexSet::new();
     for element in collected_elements {
-        collected_strings.insert(element.get_class().clone());
+        collected_strings.insert(element.class_owned());
 
         for (attribute_name, attribute_value) in element.get_attributes().iter() {
             collected_strings.insert(attribute_name.clone());
@@ -840,206 +1522,324 @@ fn collect_strings(collected_elements: &IndexSet<Element>, version: i32) -> Inde
     collected_strings
 }
 
+/// Estimates the bytes the string table and every string or string index take in a version, except string arrays which are always inline.
+fn estimate_string_size(collected_elements: &IndexSet<Element>, version: i32) -> usize {
+    let index_size = if version >= VERSION_LARGE_STRING_INDEX { 4 } else { 2 };
+    let mut size = 0;
+
+    if version >= VERSION_STRING_TABLE {
+        let collected_strings = collect_strings(collected_elements, version);
+        size += if version >= VERSION_GLOBAL_STRING_TABLE { 4 } else { 2 };
+        size += collected_strings.iter().map(|string| string.len() + 1).sum::<usize>();
+    }
+
+    let mut add_string = |value: &str, indexed: bool| size += if indexed { index_size } else { value.len() + 1 };
+    for element in collected_elements {
+        add_string(&element.get_class(), version >= VERSION_STRING_TABLE);
+        add_string(&element.name_owned().unwrap_or_default(), version >= VERSION_GLOBAL_STRING_TABLE);
+
+        for (attribute_name, attribute_value) in element.get_attributes().iter() {
+            if attribute_name == "name" {
+                continue;
+            }
+
+            add_string(attribute_name, version >= VERSION_STRING_TABLE);
+            if let AttributeValue::String(value) = &*attribute_value.get_inner() {
+                add_string(value, version >= VERSION_GLOBAL_STRING_TABLE);
+            }
+        }
+    }
+
+    size
+}
+
 struct Reader<T: BufRead> {
     buffer: T,
+    offset: u64,
+    value_offset: u64,
 }
 
 impl<T: BufRead> Reader<T> {
     fn new(buffer: T) -> Self {
-        Self { buffer }
+        Self {
+            buffer,
+            offset: 0,
+            value_offset: 0,
+        }
+    }
+
+    fn read_exact(&mut self, bytes: &mut [u8]) -> Result<(), BinarySerializationError> {
+        self.value_offset = self.offset;
+        self.buffer.read_exact(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
     }
 
     fn read_string(&mut self) -> Result<String, BinarySerializationError> {
         let mut string_buffer = Vec::new();
-        let _ = self.buffer.read_until(0, &mut string_buffer)?;
-        string_buffer.pop();
+        self.value_offset = self.offset;
+        self.offset += self.buffer.read_until(0, &mut string_buffer)? as u64;
+        if string_buffer.pop() != Some(0) {
+            return Err(Error::from(ErrorKind::UnexpectedEof).into());
+        }
         Ok(String::from_utf8_lossy(&string_buffer).into_owned())
     }
 
     fn read_byte(&mut self) -> Result<i8, BinarySerializationError> {
         let mut bytes = [0; 1];
-        self.buffer.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)?;
         Ok(i8::from_le_bytes(bytes))
     }
 
     fn read_unsigned_byte(&mut self) -> Result<u8, BinarySerializationError> {
         let mut bytes = [0; 1];
-        self.buffer.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)?;
         Ok(u8::from_le_bytes(bytes))
     }
 
     fn read_unsigned_bytes(&mut self, size: usize) -> Result<Vec<u8>, BinarySerializationError> {
-        let mut bytes = vec![0; size];
-        self.buffer.read_exact(&mut bytes)?;
+        let mut bytes = Vec::with_capacity(preallocation(size));
+        self.value_offset = self.offset;
+        (&mut self.buffer).take(size as u64).read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+        if bytes.len() != size {
+            return Err(Error::from(ErrorKind::UnexpectedEof).into());
+        }
         Ok(bytes)
     }
 
     fn read_short(&mut self) -> Result<i16, BinarySerializationError> {
         let mut bytes = [0; 2];
-        self.buffer.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)?;
         Ok(i16::from_le_bytes(bytes))
     }
 
     fn read_integer(&mut self) -> Result<i32, BinarySerializationError> {
         let mut bytes = [0; 4];
-        self.buffer.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)?;
         Ok(i32::from_le_bytes(bytes))
     }
 
     fn read_unsigned_long(&mut self) -> Result<u64, BinarySerializationError> {
         let mut bytes = [0; 8];
-        self.buffer.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)?;
         Ok(u64::from_le_bytes(bytes))
     }
 
     fn read_float(&mut self) -> Result<f32, BinarySerializationError> {
         let mut bytes = [0; 4];
-        self.buffer.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)?;
         Ok(f32::from_le_bytes(bytes))
     }
 
     fn read_uuid(&mut self) -> Result<UUID, BinarySerializationError> {
         let mut bytes = [0; 16];
-        self.buffer.read_exact(&mut bytes)?;
+        self.read_exact(&mut bytes)?;
         Ok(UUID::from_bytes_le(bytes))
     }
 
-    fn read_attribute(&mut self, version: i32, attribute_type: i8) -> Result<Attribute, BinarySerializationError> {
-        if version >= VERSION_UNSIGNED_INTEGERS {
-            if attribute_type <= ATTRIBUTE_UNSIGNED_INTEGERS_ARRAY_OFFSET {
-                return self.read_single_attribute(version, attribute_type);
-            }
+    fn skip(&mut self, size: u64) -> Result<(), BinarySerializationError> {
+        self.value_offset = self.offset;
+        let skipped = std::io::copy(&mut (&mut self.buffer).take(size), &mut std::io::sink())?;
+        self.offset += skipped;
+        if skipped != size {
+            return Err(Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        Ok(())
+    }
 
-            let array_type = attribute_type - ATTRIBUTE_UNSIGNED_INTEGERS_ARRAY_OFFSET;
-            let array_size = array_size_check(self.read_integer()?)?;
-            return self.read_array_attribute(version, array_type, array_size);
+    /// Moves past an element reference, which is an index or an external element id.
+    fn skip_element_index(&mut self) -> Result<(), BinarySerializationError> {
+        if self.read_integer()? == ELEMENT_INDEX_EXTERNAL {
+            self.read_string()?;
         }
+        Ok(())
+    }
 
-        if attribute_type <= ATTRIBUTE_INITIAL_ARRAY_OFFSET {
-            return self.read_single_attribute(version, attribute_type);
+    /// Moves past an attribute value without keeping it, so large arrays aren't read into memory.
+    fn skip_attribute(&mut self, version: i32, attribute_id: i8) -> Result<(), BinarySerializationError> {
+        let attribute_offset = self.value_offset;
+        let (attribute_type, is_array) = attribute_type_from_id(attribute_id, version).ok_or(BinarySerializationError::UnknownAttribute {
+            attribute_id,
+            offset: attribute_offset,
+        })?;
+        let (single_type, count) = if is_array {
+            let entry_id = attribute_id - attribute_array_offset(version);
+            let (single_type, _) = attribute_type_from_id(entry_id, version).expect("array types have a single type");
+            (single_type, array_size_check(self.read_integer()?, self.value_offset)?)
+        } else {
+            (attribute_type, 1)
+        };
+
+        match single_type {
+            AttributeType::Element => {
+                for _ in 0..count {
+                    self.skip_element_index()?;
+                }
+            }
+            AttributeType::String if !is_array && version >= VERSION_LARGE_STRING_INDEX => self.skip(4)?,
+            AttributeType::String if !is_array && version >= VERSION_GLOBAL_STRING_TABLE => self.skip(2)?,
+            AttributeType::String => {
+                for _ in 0..count {
+                    self.read_string()?;
+                }
+            }
+            AttributeType::Binary => {
+                for _ in 0..count {
+                    let data_size = array_size_check(self.read_integer()?, self.value_offset)?;
+                    self.skip(data_size as u64)?;
+                }
+            }
+            AttributeType::Integer | AttributeType::Float | AttributeType::Time | AttributeType::Color => self.skip(4 * count as u64)?,
+            AttributeType::Boolean | AttributeType::UByte => self.skip(count as u64)?,
+            AttributeType::Vector2 | AttributeType::ULong => self.skip(8 * count as u64)?,
+            AttributeType::Vector3 | AttributeType::Angle => self.skip(12 * count as u64)?,
+            AttributeType::ObjectId | AttributeType::Vector4 | AttributeType::Quaternion => self.skip(16 * count as u64)?,
+            AttributeType::Matrix => self.skip(64 * count as u64)?,
+            _ => {
+                return Err(BinarySerializationError::UnknownAttribute {
+                    attribute_id,
+                    offset: attribute_offset,
+                });
+            }
         }
 
-        let array_type = attribute_type - ATTRIBUTE_INITIAL_ARRAY_OFFSET;
-        let array_size = array_size_check(self.read_integer()?)?;
-        self.read_array_attribute(version, array_type, array_size)
+        Ok(())
+    }
+
+    fn read_attribute(&mut self, version: i32, attribute_id: i8) -> Result<Attribute, BinarySerializationError> {
+        let attribute_offset = self.value_offset;
+        let (attribute_type, is_array) = attribute_type_from_id(attribute_id, version).ok_or(BinarySerializationError::UnknownAttribute {
+            attribute_id,
+            offset: attribute_offset,
+        })?;
+
+        if is_array {
+            return self.read_array_attribute(attribute_type, attribute_id, attribute_offset);
+        }
+        self.read_single_attribute(attribute_type, attribute_id, attribute_offset)
     }
 
-    fn read_single_attribute(&mut self, version: i32, attribute_type: i8) -> Result<Attribute, BinarySerializationError> {
+    fn read_single_attribute(&mut self, attribute_type: AttributeType, attribute_id: i8, attribute_offset: u64) -> Result<Attribute, BinarySerializationError> {
         match attribute_type {
-            ATTRIBUTE_INTEGER_ID => Ok(self.read_integer()?.into_attribute()),
-            ATTRIBUTE_FLOAT_ID => Ok(self.read_float()?.into_attribute()),
-            ATTRIBUTE_BOOLEAN_ID => Ok((self.read_unsigned_byte()? != 0).into_attribute()),
-            ATTRIBUTE_BINARY_ID => {
-                let data_size = array_size_check(self.read_integer()?)?;
+            AttributeType::Integer => Ok(self.read_integer()?.into_attribute()),
+            AttributeType::Float => Ok(self.read_float()?.into_attribute()),
+            AttributeType::Boolean => Ok((self.read_unsigned_byte()? != 0).into_attribute()),
+            AttributeType::Binary => {
+                let data_size = array_size_check(self.read_integer()?, self.value_offset)?;
                 Ok((BinaryBlock(self.read_unsigned_bytes(data_size)?)).into_attribute())
             }
-            ATTRIBUTE_OBJECTID_ID if version < VERSION_DEPRECATE_OBJECT_ID => Ok(self.read_uuid()?.into_attribute()),
-            ATTRIBUTE_TIME_ID if version >= VERSION_DEPRECATE_OBJECT_ID => Ok(Time(self.read_integer()?).into_attribute()),
-            ATTRIBUTE_COLOR_ID => Ok(Color {
+            AttributeType::ObjectId => Ok(self.read_uuid()?.into_attribute()),
+            AttributeType::Time => Ok(Time(self.read_integer()?).into_attribute()),
+            AttributeType::Color => Ok(Color {
                 red: self.read_unsigned_byte()?,
                 green: self.read_unsigned_byte()?,
                 blue: self.read_unsigned_byte()?,
                 alpha: self.read_unsigned_byte()?,
             }
             .into_attribute()),
-            ATTRIBUTE_VECTOR2_ID => Ok(Vector2 {
+            AttributeType::Vector2 => Ok(Vector2 {
                 x: self.read_float()?,
                 y: self.read_float()?,
             }
             .into_attribute()),
-            ATTRIBUTE_VECTOR3_ID => Ok(Vector3 {
+            AttributeType::Vector3 => Ok(Vector3 {
                 x: self.read_float()?,
                 y: self.read_float()?,
                 z: self.read_float()?,
             }
             .into_attribute()),
-            ATTRIBUTE_VECTOR4_ID => Ok(Vector4 {
+            AttributeType::Vector4 => Ok(Vector4 {
                 x: self.read_float()?,
                 y: self.read_float()?,
                 z: self.read_float()?,
                 w: self.read_float()?,
             }
             .into_attribute()),
-            ATTRIBUTE_ANGLE_ID => Ok(Angle {
+            AttributeType::Angle => Ok(Angle {
                 pitch: self.read_float()?,
                 yaw: self.read_float()?,
                 roll: self.read_float()?,
             }
             .into_attribute()),
-            ATTRIBUTE_QUATERNION_ID => Ok(Quaternion {
+            AttributeType::Quaternion => Ok(Quaternion {
                 x: self.read_float()?,
                 y: self.read_float()?,
                 z: self.read_float()?,
                 w: self.read_float()?,
             }
             .into_attribute()),
-            ATTRIBUTE_MATRIX_ID => Ok(Matrix([
+            AttributeType::Matrix => Ok(Matrix([
                 [self.read_float()?, self.read_float()?, self.read_float()?, self.read_float()?],
                 [self.read_float()?, self.read_float()?, self.read_float()?, self.read_float()?],
                 [self.read_float()?, self.read_float()?, self.read_float()?, self.read_float()?],
                 [self.read_float()?, self.read_float()?, self.read_float()?, self.read_float()?],
             ])
             .into_attribute()),
-            ATTRIBUTE_ULONG_ID if version >= VERSION_UNSIGNED_INTEGERS => Ok(self.read_unsigned_long()?.into_attribute()),
-            ATTRIBUTE_UBYTE_ID if version >= VERSION_UNSIGNED_INTEGERS => Ok(self.read_unsigned_byte()?.into_attribute()),
-            _ => Err(BinarySerializationError::UnknownAttribute { attribute_id: attribute_type }),
+            AttributeType::ULong => Ok(self.read_unsigned_long()?.into_attribute()),
+            AttributeType::UByte => Ok(self.read_unsigned_byte()?.into_attribute()),
+            _ => Err(BinarySerializationError::UnknownAttribute {
+                attribute_id,
+                offset: attribute_offset,
+            }),
         }
     }
 
-    fn read_array_attribute(&mut self, version: i32, attribute_type: i8, size: usize) -> Result<Attribute, BinarySerializationError> {
+    fn read_array_attribute(&mut self, attribute_type: AttributeType, attribute_id: i8, attribute_offset: u64) -> Result<Attribute, BinarySerializationError> {
+        let size = array_size_check(self.read_integer()?, self.value_offset)?;
         match attribute_type {
-            ATTRIBUTE_INTEGER_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::IntegerArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(self.read_integer()?);
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_FLOAT_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::FloatArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(self.read_float()?);
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_BOOLEAN_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::BooleanArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(self.read_unsigned_byte()? != 0);
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_STRING_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::StringArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(self.read_string()?);
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_BINARY_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::BinaryArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
-                    let data_size = array_size_check(self.read_integer()?)?;
+                    let data_size = array_size_check(self.read_integer()?, self.value_offset)?;
                     attribute_array.push(BinaryBlock(self.read_unsigned_bytes(data_size)?));
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_OBJECTID_ID if version < VERSION_DEPRECATE_OBJECT_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::ObjectIdArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(self.read_uuid()?);
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_TIME_ID if version >= VERSION_DEPRECATE_OBJECT_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::TimeArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Time(self.read_integer()?));
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_COLOR_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::ColorArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Color {
                         red: self.read_unsigned_byte()?,
@@ -1050,8 +1850,8 @@ impl<T: BufRead> Reader<T> {
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_VECTOR2_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::Vector2Array => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Vector2 {
                         x: self.read_float()?,
@@ -1060,8 +1860,8 @@ impl<T: BufRead> Reader<T> {
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_VECTOR3_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::Vector3Array => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Vector3 {
                         x: self.read_float()?,
@@ -1071,8 +1871,8 @@ impl<T: BufRead> Reader<T> {
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_VECTOR4_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::Vector4Array => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Vector4 {
                         x: self.read_float()?,
@@ -1083,8 +1883,8 @@ impl<T: BufRead> Reader<T> {
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_ANGLE_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::AngleArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Angle {
                         pitch: self.read_float()?,
@@ -1094,8 +1894,8 @@ impl<T: BufRead> Reader<T> {
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_QUATERNION_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::QuaternionArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Quaternion {
                         x: self.read_float()?,
@@ -1106,8 +1906,8 @@ impl<T: BufRead> Reader<T> {
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_MATRIX_ID => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::MatrixArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(Matrix([
                         [self.read_float()?, self.read_float()?, self.read_float()?, self.read_float()?],
@@ -1118,32 +1918,287 @@ impl<T: BufRead> Reader<T> {
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_ULONG_ID if version >= VERSION_UNSIGNED_INTEGERS => {
-                let mut attribute_array = Vec::with_capacity(size);
+            AttributeType::ULongArray => {
+                let mut attribute_array = Vec::with_capacity(preallocation(size));
                 for _ in 0..size {
                     attribute_array.push(self.read_unsigned_long()?);
                 }
                 Ok(attribute_array.into_attribute())
             }
-            ATTRIBUTE_UBYTE_ID if version >= VERSION_UNSIGNED_INTEGERS => Ok(self.read_unsigned_bytes(size)?.into_attribute()),
-            _ => Err(BinarySerializationError::UnknownAttribute { attribute_id: attribute_type }),
+            AttributeType::UByteArray => Ok(self.read_unsigned_bytes(size)?.into_attribute()),
+            _ => Err(BinarySerializationError::UnknownAttribute {
+                attribute_id,
+                offset: attribute_offset,
+            }),
+        }
+    }
+}
+
+/// Reads the binary body after the header up to the end of the element table.
+///
+/// `read_element` is called with the class, name, and id of each element in table order.
+///
+/// # Returns
+/// The string table, which the attributes after the element table index into.
+fn read_element_table<T: BufRead>(
+    reader: &mut Reader<T>,
+    version: i32,
+    max_elements: usize,
+    mut read_element: impl FnMut(String, String, UUID),
+) -> Result<Vec<String>, BinarySerializationError> {
+    reader.read_string()?;
+
+    if version >= VERSION_PREFIX_ELEMENT && reader.read_integer()? != 0 {
+        let attribute_count = array_size_check(reader.read_integer()?, reader.value_offset)?;
+        for _ in 0..attribute_count {
+            reader.read_string()?;
+            let attribute_id = reader.read_byte()?;
+            match attribute_type_from_id(attribute_id, version) {
+                Some((AttributeType::Element | AttributeType::ElementArray, _)) => {
+                    return Err(BinarySerializationError::InvalidPrefixElementAttribute { offset: reader.value_offset });
+                }
+                Some((AttributeType::String, _)) => {
+                    reader.read_string()?;
+                }
+                _ => {
+                    reader.read_attribute(version, attribute_id)?;
+                }
+            }
+        }
+    }
+
+    let string_table_size = if version >= VERSION_GLOBAL_STRING_TABLE {
+        array_size_check(reader.read_integer()?, reader.value_offset)?
+    } else if version >= VERSION_STRING_TABLE {
+        array_size_check(reader.read_short()? as i32, reader.value_offset)?
+    } else {
+        0
+    };
+    let mut string_table = Vec::with_capacity(preallocation(string_table_size));
+    for _ in 0..string_table_size {
+        string_table.push(reader.read_string()?);
+    }
+
+    let element_size = array_size_check(reader.read_integer()?, reader.value_offset)?;
+    if element_size > max_elements {
+        return Err(BinarySerializationError::InvalidElementTableLength {
+            length: element_size,
+            max: max_elements,
+            offset: reader.value_offset,
+        });
+    }
+    for _ in 0..element_size {
+        let element_class = if version >= VERSION_LARGE_STRING_INDEX {
+            get_string_table_index(reader.read_integer()?, &string_table, reader.value_offset)?
+        } else if version >= VERSION_STRING_TABLE {
+            get_string_table_index(reader.read_short()? as i32, &string_table, reader.value_offset)?
+        } else {
+            reader.read_string()?
+        };
+
+        if (VERSION_LINK_TYPE..VERSION_DEPRECATE_LINK_TYPE).contains(&version) {
+            reader.read_integer()?;
+        }
+
+        let element_name = if version >= VERSION_LARGE_STRING_INDEX {
+            get_string_table_index(reader.read_integer()?, &string_table, reader.value_offset)?
+        } else if version >= VERSION_GLOBAL_STRING_TABLE {
+            get_string_table_index(reader.read_short()? as i32, &string_table, reader.value_offset)?
+        } else {
+            reader.read_string()?
+        };
+
+        read_element(element_class, element_name, reader.read_uuid()?);
+    }
+
+    Ok(string_table)
+}
+
+/// Reads the binary body after the header into the element table.
+///
+/// Elements are pushed to `elements` as soon as the element table is read, so the caller keeps them if reading the attributes fails.
+/// Checks the version and then the encoding given to a read entry point of [BinarySerializer].
+fn check_encoding(encoding: &str, version: i32) -> Result<(), BinarySerializationError> {
+    if !(1..=BinarySerializer::version()).contains(&version) {
+        return Err(BinarySerializationError::InvalidVersion { version });
+    }
+
+    if encoding != BinarySerializer::name() {
+        return Err(BinarySerializationError::WrongEncoding);
+    }
+
+    Ok(())
+}
+
+fn read_elements(
+    buffer: &mut impl BufRead,
+    version: i32,
+    max_elements: usize,
+    elements: &mut Vec<Element>,
+    recovery: &mut Recovery,
+    resolver: &mut impl FnMut(UUID) -> Option<Element>,
+) -> Result<(), BinarySerializationError> {
+    let mut reader = Reader::new(buffer);
+    let mut classes = ClassInterner::default();
+    let string_table = read_element_table(&mut reader, version, max_elements, |element_class, element_name, element_id| {
+        let mut new_element = Element::full(classes.intern(&element_class), element_id);
+        new_element.set_attribute("name", element_name.into_attribute());
+        elements.push(new_element);
+    })?;
+
+    for element_index in 0..elements.len() {
+        let attribute_count = array_size_check(reader.read_integer()?, reader.value_offset)?;
+        let mut current_element = Element::clone(&elements[element_index]);
+        for _ in 0..attribute_count {
+            let attribute_name = if version >= VERSION_LARGE_STRING_INDEX {
+                recovery.check(get_string_table_index(reader.read_integer()?, &string_table, reader.value_offset))?
+            } else if version >= VERSION_STRING_TABLE {
+                recovery.check(get_string_table_index(reader.read_short()? as i32, &string_table, reader.value_offset))?
+            } else {
+                Some(reader.read_string()?)
+            };
+            let attribute_id = reader.read_byte()?;
+            let attribute_value = match attribute_type_from_id(attribute_id, version) {
+                Some((AttributeType::Element, _)) => Some(read_element_index(&mut reader, elements, recovery, resolver)?.into_attribute()),
+                Some((AttributeType::ElementArray, _)) => {
+                    let array_size = array_size_check(reader.read_integer()?, reader.value_offset)?;
+                    let mut attribute_array = Vec::with_capacity(preallocation(array_size));
+                    for _ in 0..array_size {
+                        attribute_array.push(read_element_index(&mut reader, elements, recovery, resolver)?);
+                    }
+                    Some(attribute_array.into_attribute())
+                }
+                Some((AttributeType::String, _)) => (if version >= VERSION_LARGE_STRING_INDEX {
+                    recovery.check(get_string_table_index(reader.read_integer()?, &string_table, reader.value_offset))?
+                } else if version >= VERSION_GLOBAL_STRING_TABLE {
+                    recovery.check(get_string_table_index(reader.read_short()? as i32, &string_table, reader.value_offset))?
+                } else {
+                    Some(reader.read_string()?)
+                })
+                .map(String::into_attribute),
+                _ => Some(reader.read_attribute(version, attribute_id)?),
+            };
+            if let (Some(attribute_name), Some(attribute_value)) = (attribute_name, attribute_value) {
+                current_element.set_attribute(attribute_name, attribute_value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads an element attribute value, which is an index into the element table or an external element id.
+///
+/// External elements are the element from the resolver, or an empty placeholder with the id if it returns [None].
+fn read_element_index<T: BufRead>(
+    reader: &mut Reader<T>,
+    elements: &[Element],
+    recovery: &mut Recovery,
+    resolver: &mut impl FnMut(UUID) -> Option<Element>,
+) -> Result<Option<Element>, BinarySerializationError> {
+    Ok(match reader.read_integer()? {
+        index if index < ELEMENT_INDEX_EXTERNAL || index >= elements.len() as i32 => {
+            recovery.check::<()>(Err(BinarySerializationError::InvalidElementTableIndex {
+                index,
+                size: elements.len(),
+                offset: reader.value_offset,
+            }))?;
+            None
+        }
+        ELEMENT_INDEX_NULL => None,
+        ELEMENT_INDEX_EXTERNAL => {
+            let element_id = reader.read_string()?;
+            recovery
+                .check(UUID::from_str(&element_id).map_err(BinarySerializationError::from))?
+                .map(|element_id| resolver(element_id).unwrap_or_else(|| Element::full(Element::class_name(), element_id)))
+        }
+        index => Some(Element::clone(&elements[index as usize])),
+    })
+}
+
+/// Decides which errors stop deserialization.
+///
+/// When enabled, errors that leave the reader at a known position are recorded instead of returned.
+struct Recovery {
+    enabled: bool,
+    skipped: Vec<BinarySerializationError>,
+}
+
+impl Recovery {
+    fn disabled() -> Self {
+        Self {
+            enabled: false,
+            skipped: Vec::new(),
+        }
+    }
+
+    fn enabled() -> Self {
+        Self {
+            enabled: true,
+            skipped: Vec::new(),
+        }
+    }
+
+    fn check<V>(&mut self, result: Result<V, BinarySerializationError>) -> Result<Option<V>, BinarySerializationError> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if self.enabled => {
+                self.skipped.push(error);
+                Ok(None)
+            }
+            Err(error) => Err(error),
         }
     }
 }
 
-fn array_size_check(size: i32) -> Result<usize, BinarySerializationError> {
+const CHECKSUM_MAGIC: &[u8; 4] = b"DCRC";
+
+/// The CRC32 (IEEE) of the data.
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut index = 0;
+        while index < 256 {
+            let mut value = index as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                value = if value & 1 == 1 { (value >> 1) ^ 0xEDB88320 } else { value >> 1 };
+                bit += 1;
+            }
+            table[index] = value;
+            index += 1;
+        }
+        table
+    };
+
+    !data
+        .iter()
+        .fold(u32::MAX, |crc, &byte| TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+/// The most entries a length read from the file reserves up front.
+///
+/// Lengths come from the file, so a small damaged or hostile file can claim billions of entries.
+/// Vectors start at most this large and grow as the entries are actually read, so a lying length fails at the end of the buffer instead of on allocation.
+const MAX_PREALLOCATED_ENTRIES: usize = 4096;
+
+fn preallocation(size: usize) -> usize {
+    size.min(MAX_PREALLOCATED_ENTRIES)
+}
+
+fn array_size_check(size: i32, offset: u64) -> Result<usize, BinarySerializationError> {
     if size < 0 {
-        return Err(BinarySerializationError::InvalidArraySize);
+        return Err(BinarySerializationError::InvalidArraySize { offset });
     }
     Ok(size as usize)
 }
 
-fn get_string_table_index(index: i32, table: &[String]) -> Result<String, BinarySerializationError> {
+fn get_string_table_index(index: i32, table: &[String], offset: u64) -> Result<String, BinarySerializationError> {
     if index == -1 {
         return Ok(String::new());
     }
     if index < 0 || index as usize >= table.len() {
-        return Err(BinarySerializationError::InvalidStringTableIndex);
+        return Err(BinarySerializationError::InvalidStringTableIndex { offset });
     }
     Ok(table[index as usize].clone())
 }
@@ -6,7 +6,8 @@ use uuid::Uuid as UUID;
 
 use crate::{
     attribute::{Angle, Attribute, AttributeType, AttributeValue, BinaryBlock, Color, Matrix, Quaternion, Time, Vector2, Vector3, Vector4},
-    element::Element,
+    element::{ClassInterner, Element},
+    serializers::NonFiniteFloats,
     serializing::{Header, Serializer},
 };
 
@@ -51,12 +52,18 @@ pub enum KeyValues2SerializationError {
     ParseUUIDError(usize, usize),
     #[error("Time Value Out Of Range At {0},{1} - Min {min} Max {max}", min = Time(i32::MIN).as_seconds(), max = Time(i32::MAX).as_seconds())]
     TimeAttributeOutOFRange(usize, usize),
+    #[error("Trailing Content After The Last Element At {0},{1}")]
+    TrailingContent(usize, usize),
+    #[error("Unknown Attribute Type \"{token}\" At {line},{column}")]
+    UnknownAttributeType { token: String, line: usize, column: usize },
     #[error("Invalid Id Attribute Type At {0},{1}")]
     InvalidNameAttributeType(usize, usize),
     #[error("Attribute \"name\" In Element \"{}\" Is Not Type String", element.get_id())]
     InvalidNameAttribute { element: Element },
     #[error("Attribute \"id\" In Element \"{}\" Can't Be Type ObjectId", element.get_id())]
     InvalidIdAttribute { element: Element },
+    #[error("Attribute \"{}\" In Element \"{}\" Has A Non-Finite Float", attribute, element.get_id())]
+    NonFiniteFloat { attribute: String, element: Element },
     #[error("Element Generated With Existing Id")]
     DuplicateGeneratedElementId,
     #[error("Element Id \"{0}\" Already Exists")]
@@ -65,64 +72,244 @@ pub enum KeyValues2SerializationError {
     InvalidAttributeValue(usize, usize),
     #[error("No Elements In File")]
     NoElements,
+    #[error("Inline Elements Are Nested Deeper Than The Max Depth {depth}")]
+    MaxDepthExceeded { depth: usize },
+    #[error("IO Error After {} Elements Were Written: {source}", context.elements_written)]
+    Write { source: IOError, context: WriteContext },
+}
+
+/// How far [KeyValues2Serializer] or [KeyValues2FlatSerializer] got before a write failed, in [KeyValues2SerializationError::Write].
+///
+/// # Example
+/// ```
+/// use std::io::{Error, ErrorKind, Write};
+/// use datamodel::{Element, Header, Serializer, serializers::{KeyValues2SerializationError, KeyValues2Serializer}};
+///
+/// struct Full(usize);
+///
+/// impl Write for Full {
+///     fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+///         if self.0 < bytes.len() {
+///             return Err(Error::from(ErrorKind::StorageFull));
+///         }
+///         self.0 -= bytes.len();
+///         Ok(bytes.len())
+///     }
+///
+///     fn flush(&mut self) -> std::io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// let mut root = Element::named("root");
+/// let child = Element::named("child");
+/// root.link("child", &child);
+///
+/// let mut written = Vec::new();
+/// KeyValues2Serializer::serialize(&mut written, &Header::default(), &root).unwrap();
+/// let mut full = Full(written.len() - 8);
+/// match KeyValues2Serializer::serialize(&mut full, &Header::default(), &root) {
+///     Err(KeyValues2SerializationError::Write { context, .. }) => {
+///         assert_eq!(context.elements_written, 1);
+///         assert_eq!(context.current_element_id, Some(*root.get_id()));
+///     }
+///     result => panic!("{result:?}"),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteContext {
+    /// The number of elements that had all their attributes written.
+    pub elements_written: usize,
+    /// The id of the element that was being written, or [None] if the write failed outside an element, like in the header.
+    pub current_element_id: Option<UUID>,
+}
+
+/// Options that change how [KeyValues2Serializer] and [KeyValues2FlatSerializer] handle a file.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyValues2Options {
+    /// How `NaN` and infinite floats are written, [NonFiniteFloats::Error] returns [KeyValues2SerializationError::NonFiniteFloat].
+    ///
+    /// Defaults to [NonFiniteFloats::Write], which writes them as before and can't be read back.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, Vector3}};
+    /// use datamodel::serializers::{KeyValues2Options, KeyValues2SerializationError, KeyValues2Serializer, NonFiniteFloats};
+    ///
+    /// let write = |root: &Element, non_finite_floats: NonFiniteFloats| {
+    ///     let options = KeyValues2Options { non_finite_floats, ..Default::default() };
+    ///     let mut buffer = Vec::new();
+    ///     KeyValues2Serializer::serialize_with_options(&mut buffer, &Header::default(), root, KeyValues2Serializer::version(), options).map(|_| buffer)
+    /// };
+    ///
+    /// let mut scalar = Element::named("scalar");
+    /// scalar.set_attribute("scale", f32::NAN.into_attribute());
+    /// let mut array = Element::named("array");
+    /// array.set_attribute("weights", vec![1.0f32, f32::INFINITY].into_attribute());
+    /// let mut vector = Element::named("vector");
+    /// vector.set_attribute("position", Vector3 { x: 1.0, y: f32::NEG_INFINITY, z: 3.0 }.into_attribute());
+    ///
+    /// for (root, attribute) in [(&scalar, "scale"), (&array, "weights"), (&vector, "position")] {
+    ///     // The default writes them like before.
+    ///     let mut buffer = Vec::new();
+    ///     KeyValues2Serializer::serialize(&mut buffer, &Header::default(), root).unwrap();
+    ///     assert_eq!(buffer, write(root, NonFiniteFloats::Write).unwrap());
+    ///
+    ///     match write(root, NonFiniteFloats::Error) {
+    ///         Err(KeyValues2SerializationError::NonFiniteFloat { attribute: name, element }) => {
+    ///             assert_eq!(name, attribute);
+    ///             assert_eq!(element, *root);
+    ///         }
+    ///         result => panic!("{result:?}"),
+    ///     }
+    /// }
+    ///
+    /// let (_, read) = datamodel::deserialize_slice(&write(&scalar, NonFiniteFloats::Sanitize).unwrap()).unwrap();
+    /// assert_eq!(read.get_copied::<f32>("scale"), Some(0.0));
+    /// let (_, read) = datamodel::deserialize_slice(&write(&array, NonFiniteFloats::Sanitize).unwrap()).unwrap();
+    /// assert_eq!(read.get_attribute("weights").unwrap().get_value::<Vec<f32>>().as_deref(), Some(&vec![1.0, 0.0]));
+    /// let (_, read) = datamodel::deserialize_slice(&write(&vector, NonFiniteFloats::Sanitize).unwrap()).unwrap();
+    /// let position = read.get_copied::<Vector3>("position").unwrap();
+    /// assert_eq!((position.x, position.y, position.z), (1.0, 0.0, 3.0));
+    /// ```
+    pub non_finite_floats: NonFiniteFloats,
+    /// The deepest inline elements can be nested when reading before [KeyValues2SerializationError::MaxDepthExceeded] is returned.
+    ///
+    /// Each nested inline element is read recursively, so this stops untrusted files from overflowing the stack.
+    /// Defaults to 128, which fits in a 2 MiB thread stack in debug builds.
+    /// Release builds use much less stack for each level, so it can be raised there.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Header, Serializer, serializers::{KeyValues2Options, KeyValues2SerializationError, KeyValues2Serializer}};
+    ///
+    /// let nested = |depth: usize| {
+    ///     let mut file = String::from("<!-- dmx encoding keyvalues2 4 format dmx 22 -->\n\"DmElement\"\n{\n");
+    ///     for _ in 0..depth {
+    ///         file.push_str("\"child\" \"DmElement\"\n{\n");
+    ///     }
+    ///     file.push_str(&"}\n".repeat(depth + 1));
+    ///     file
+    /// };
+    ///
+    /// // The default returns an error on a 2 MiB thread instead of overflowing its stack.
+    /// let deep = nested(1000);
+    /// let exceeded = std::thread::Builder::new()
+    ///     .stack_size(2 * 1024 * 1024)
+    ///     .spawn(move || {
+    ///         let mut data = deep.as_bytes();
+    ///         let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    ///         let result = KeyValues2Serializer::deserialize(&mut data, encoding, version);
+    ///         matches!(result, Err(KeyValues2SerializationError::MaxDepthExceeded { depth: 128 }))
+    ///     })
+    ///     .unwrap()
+    ///     .join()
+    ///     .unwrap();
+    /// assert!(exceeded);
+    ///
+    /// let file = nested(20);
+    /// let options = KeyValues2Options { max_depth: 16, ..Default::default() };
+    /// let mut data = file.as_bytes();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// let error = KeyValues2Serializer::deserialize_with_options(&mut data, encoding, version, options).unwrap_err();
+    /// assert!(matches!(error, KeyValues2SerializationError::MaxDepthExceeded { depth: 16 }));
+    ///
+    /// let options = KeyValues2Options { max_depth: 32, ..Default::default() };
+    /// let mut data = file.as_bytes();
+    /// let (_, encoding, version) = Header::from_buffer(&mut data).unwrap();
+    /// assert!(KeyValues2Serializer::deserialize_with_options(&mut data, encoding, version, options).is_ok());
+    /// ```
+    pub max_depth: usize,
+    /// Accept values that aren't quoted and `true` or `false` for booleans when reading.
+    ///
+    /// Valve's tools always quote values, but some hand written and third party files don't.
+    /// An unquoted token ends at whitespace, a quote, a comma, or a brace or bracket.
+    pub lenient: bool,
+    /// Leave null entries out of element arrays when writing.
+    ///
+    /// This is lossy, the arrays get shorter and the entries after a null move to lower indices.
+    pub compact_element_arrays: bool,
+    /// Write without indentation or blank lines between elements and end lines with `\n` instead of `\r\n`.
+    ///
+    /// The reader ignores whitespace between tokens, so compact files read back the same.
+    pub compact: bool,
+}
+
+impl Default for KeyValues2Options {
+    fn default() -> Self {
+        Self {
+            non_finite_floats: NonFiniteFloats::Write,
+            max_depth: 128,
+            lenient: false,
+            compact_element_arrays: false,
+            compact: false,
+        }
+    }
 }
 
 struct StringWriter<T: Write> {
     buffer: T,
     tab_index: usize,
+    options: KeyValues2Options,
+    context: WriteContext,
 }
 
 impl<T: Write> StringWriter<T> {
-    fn new(buffer: T) -> Self {
-        Self { buffer, tab_index: 0 }
+    fn new(buffer: T, options: KeyValues2Options) -> Self {
+        Self {
+            buffer,
+            tab_index: 0,
+            options,
+            context: WriteContext::default(),
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), KeyValues2SerializationError> {
+        self.buffer
+            .write_all(bytes)
+            .map_err(|source| KeyValues2SerializationError::Write { source, context: self.context })
     }
 
     fn write_header(&mut self, line: &str) -> Result<(), KeyValues2SerializationError> {
-        self.buffer.write_all(line.as_bytes())?;
-        Ok(())
+        self.write_bytes(line.as_bytes())
     }
 
     fn write_tabs(&mut self) -> Result<(), KeyValues2SerializationError> {
-        if self.tab_index == 0 {
+        if self.tab_index == 0 || self.options.compact {
             return Ok(());
         }
-        self.buffer.write_all(&vec![b'\t'; self.tab_index])?;
-        Ok(())
+        self.write_bytes(&vec![b'\t'; self.tab_index])
     }
 
     fn write_line(&mut self, line: &str) -> Result<(), KeyValues2SerializationError> {
+        if self.options.compact && line.is_empty() {
+            return Ok(());
+        }
         self.write_tabs()?;
-        self.buffer.write_all(line.as_bytes())?;
-        self.buffer.write_all(b"\r\n")?;
-        Ok(())
+        self.write_bytes(line.as_bytes())?;
+        self.write_bytes(if self.options.compact { b"\n" } else { b"\r\n" })
     }
 
     fn write_open_brace(&mut self) -> Result<(), KeyValues2SerializationError> {
-        self.write_tabs()?;
-        self.buffer.write_all(b"{\r\n")?;
+        self.write_line("{")?;
         self.tab_index += 1;
         Ok(())
     }
 
     fn write_close_brace(&mut self) -> Result<(), KeyValues2SerializationError> {
         self.tab_index -= 1;
-        self.write_tabs()?;
-        self.buffer.write_all(b"}\r\n")?;
-        Ok(())
+        self.write_line("}")
     }
 
     fn write_open_bracket(&mut self) -> Result<(), KeyValues2SerializationError> {
-        self.write_tabs()?;
-        self.buffer.write_all(b"[\r\n")?;
+        self.write_line("[")?;
         self.tab_index += 1;
         Ok(())
     }
 
     fn write_close_bracket(&mut self) -> Result<(), KeyValues2SerializationError> {
         self.tab_index -= 1;
-        self.write_tabs()?;
-        self.buffer.write_all(b"]\r\n")?;
-        Ok(())
+        self.write_line("]")
     }
 
     fn write_attributes(&mut self, root: &Element, collected_elements: &IndexMap<Element, usize>) -> Result<(), KeyValues2SerializationError> {
@@ -141,8 +328,10 @@ impl<T: Write> StringWriter<T> {
             };
         }
 
+        let parent_element_id = self.context.current_element_id.replace(*root.get_id());
+
         for (name, attribute) in root.get_attributes().iter() {
-            let attribute_type_name = Self::get_attribute_type_name(attribute);
+            let attribute_type_name = attribute.get_type().type_name();
 
             if name == "name" && attribute.get_type() != AttributeType::String {
                 return Err(KeyValues2SerializationError::InvalidNameAttribute { element: Element::clone(root) });
@@ -152,7 +341,23 @@ impl<T: Write> StringWriter<T> {
                 return Err(KeyValues2SerializationError::InvalidIdAttribute { element: Element::clone(root) });
             }
 
-            match &*attribute.get_inner() {
+            let attribute_value = attribute.get_inner();
+            let Some(attribute_value) = self.options.non_finite_floats.apply(&attribute_value) else {
+                return Err(KeyValues2SerializationError::NonFiniteFloat {
+                    attribute: name.clone(),
+                    element: Element::clone(root),
+                });
+            };
+            let compacted_value;
+            let attribute_value = match &*attribute_value {
+                AttributeValue::ElementArray(elements) if self.options.compact_element_arrays && elements.iter().any(Option::is_none) => {
+                    compacted_value = AttributeValue::ElementArray(elements.iter().flatten().cloned().map(Some).collect());
+                    &compacted_value
+                }
+                attribute_value => attribute_value,
+            };
+
+            match attribute_value {
                 AttributeValue::Element(element) => {
                     if let Some(element) = element {
                         let &count = collected_elements.get(element).unwrap();
@@ -227,10 +432,9 @@ impl<T: Write> StringWriter<T> {
                     write_attribute_string!(self, name, attribute_type_name)?;
                     self.write_line("\"")?;
                     self.tab_index += 1;
-                    self.write_line(&format!("{} {} {} {}", matrix.0[0][0], matrix.0[0][1], matrix.0[0][2], matrix.0[0][3]))?;
-                    self.write_line(&format!("{} {} {} {}", matrix.0[1][0], matrix.0[1][1], matrix.0[1][2], matrix.0[1][3]))?;
-                    self.write_line(&format!("{} {} {} {}", matrix.0[2][0], matrix.0[2][1], matrix.0[2][2], matrix.0[2][3]))?;
-                    self.write_line(&format!("{} {} {} {}", matrix.0[3][0], matrix.0[3][1], matrix.0[3][2], matrix.0[3][3]))?;
+                    for [x, y, z, w] in matrix.0 {
+                        self.write_line(&format!("{x} {y} {z} {w}"))?;
+                    }
                     self.tab_index -= 1;
                     self.write_line("\"")?;
                 }
@@ -461,31 +665,17 @@ impl<T: Write> StringWriter<T> {
                         for matrix in matrixes {
                             self.write_line("\"")?;
                             self.tab_index += 1;
-                            self.write_line(&format!("{} {} {} {}", matrix.0[0][0], matrix.0[0][1], matrix.0[0][2], matrix.0[0][3]))?;
-                            self.write_line(&format!("{} {} {} {}", matrix.0[1][0], matrix.0[1][1], matrix.0[1][2], matrix.0[1][3]))?;
-                            self.write_line(&format!("{} {} {} {}", matrix.0[2][0], matrix.0[2][1], matrix.0[2][2], matrix.0[2][3]))?;
-                            self.write_line(&format!("{} {} {} {}", matrix.0[3][0], matrix.0[3][1], matrix.0[3][2], matrix.0[3][3]))?;
+                            for [x, y, z, w] in matrix.0 {
+                                self.write_line(&format!("{x} {y} {z} {w}"))?;
+                            }
                             self.tab_index -= 1;
                             self.write_line("\",")?;
                         }
                         self.write_line("\"")?;
                         self.tab_index += 1;
-                        self.write_line(&format!(
-                            "{} {} {} {}",
-                            last_matrix.0[0][0], last_matrix.0[0][1], last_matrix.0[0][2], last_matrix.0[0][3]
-                        ))?;
-                        self.write_line(&format!(
-                            "{} {} {} {}",
-                            last_matrix.0[1][0], last_matrix.0[1][1], last_matrix.0[1][2], last_matrix.0[1][3]
-                        ))?;
-                        self.write_line(&format!(
-                            "{} {} {} {}",
-                            last_matrix.0[2][0], last_matrix.0[2][1], last_matrix.0[2][2], last_matrix.0[2][3]
-                        ))?;
-                        self.write_line(&format!(
-                            "{} {} {} {}",
-                            last_matrix.0[3][0], last_matrix.0[3][1], last_matrix.0[3][2], last_matrix.0[3][3]
-                        ))?;
+                        for [x, y, z, w] in last_matrix.0 {
+                            self.write_line(&format!("{x} {y} {z} {w}"))?;
+                        }
                         self.tab_index -= 1;
                         self.write_line("\"")?;
                     }
@@ -515,46 +705,10 @@ impl<T: Write> StringWriter<T> {
                 }
             }
         }
-        Ok(())
-    }
 
-    fn get_attribute_type_name(attribute: &Attribute) -> &'static str {
-        match attribute.get_type() {
-            AttributeType::Element => "element",
-            AttributeType::Integer => "int",
-            AttributeType::Float => "float",
-            AttributeType::Boolean => "bool",
-            AttributeType::String => "string",
-            AttributeType::Binary => "binary",
-            AttributeType::ObjectId => "elementid",
-            AttributeType::Time => "time",
-            AttributeType::Color => "color",
-            AttributeType::Vector2 => "vector2",
-            AttributeType::Vector3 => "vector3",
-            AttributeType::Vector4 => "vector4",
-            AttributeType::Angle => "qangle",
-            AttributeType::Quaternion => "quaternion",
-            AttributeType::Matrix => "matrix",
-            AttributeType::ULong => "uint64",
-            AttributeType::UByte => "uint8",
-            AttributeType::ElementArray => "element_array",
-            AttributeType::IntegerArray => "int_array",
-            AttributeType::FloatArray => "float_array",
-            AttributeType::BooleanArray => "bool_array",
-            AttributeType::StringArray => "string_array",
-            AttributeType::BinaryArray => "binary_array",
-            AttributeType::ObjectIdArray => "elementid_array",
-            AttributeType::TimeArray => "time_array",
-            AttributeType::ColorArray => "color_array",
-            AttributeType::Vector2Array => "vector2_array",
-            AttributeType::Vector3Array => "vector3_array",
-            AttributeType::Vector4Array => "vector4_array",
-            AttributeType::AngleArray => "qangle_array",
-            AttributeType::QuaternionArray => "quaternion_array",
-            AttributeType::MatrixArray => "matrix_array",
-            AttributeType::ULongArray => "uint64_array",
-            AttributeType::UByteArray => "uint8_array",
-        }
+        self.context.elements_written += 1;
+        self.context.current_element_id = parent_element_id;
+        Ok(())
     }
 
     fn format_escape_characters(&self, text: &str) -> String {
@@ -607,15 +761,35 @@ struct StringReader<T: BufRead> {
     current_line: String,
     line: usize,
     column: usize,
+    classes: ClassInterner,
+    depth: usize,
+    max_depth: usize,
+    lenient: bool,
+    /// The bytes read from the buffer, and where the current line starts in them.
+    consumed: usize,
+    line_start: usize,
+    /// Stop at a header line between top level elements, see [KeyValues2Serializer::deserialize_document].
+    stop_at_header: bool,
+    top_level: bool,
+    next_header: Option<usize>,
 }
 
 impl<T: BufRead> StringReader<T> {
-    fn new(buffer: T) -> Self {
+    fn new(buffer: T, options: KeyValues2Options) -> Self {
         Self {
             buffer,
             current_line: String::new(),
             line: 1,
             column: 0,
+            classes: ClassInterner::default(),
+            depth: 0,
+            max_depth: options.max_depth,
+            lenient: options.lenient,
+            consumed: 0,
+            line_start: 0,
+            stop_at_header: false,
+            top_level: false,
+            next_header: None,
         }
     }
 
@@ -710,6 +884,11 @@ impl<T: BufRead> StringReader<T> {
                         continue;
                     }
 
+                    if self.stop_at_header && self.top_level && token.is_none() && Header::from_string(self.current_line.clone()).is_ok() {
+                        self.next_header = Some(self.line_start);
+                        return Ok(None);
+                    }
+
                     self.current_line = match self.next_line()? {
                         Some(line) => line,
                         None => return Ok(None),
@@ -778,6 +957,19 @@ impl<T: BufRead> StringReader<T> {
                         continue;
                     }
 
+                    if self.lenient {
+                        let mut bare_token = String::from(character);
+                        while let Some(&next_character) = line_characters.peek() {
+                            if next_character.is_whitespace() || matches!(next_character, '"' | ',' | '{' | '}' | '[' | ']') {
+                                break;
+                            }
+                            bare_token.push(next_character);
+                            line_characters.next();
+                            self.column += 1;
+                        }
+                        return Ok(Some(ReadToken::String(bare_token)));
+                    }
+
                     return Err(KeyValues2SerializationError::UnknownToken(character, self.line, self.column));
                 }
                 None => {
@@ -806,6 +998,8 @@ impl<T: BufRead> StringReader<T> {
         if byte_count == 0 {
             return Ok(None);
         }
+        self.line_start = self.consumed;
+        self.consumed += byte_count;
         Ok(Some(line))
     }
 
@@ -814,8 +1008,16 @@ impl<T: BufRead> StringReader<T> {
         collected_elements: &mut IndexMap<UUID, Element>,
         element_remap: &mut IndexMap<Element, Vec<(String, ElementAttributeRemap)>>,
     ) -> Result<Option<Element>, KeyValues2SerializationError> {
-        let element_class = match self.next_token()? {
+        // Anything after the last element that can't start another element is trailing content.
+        let has_elements = !collected_elements.is_empty();
+        self.top_level = true;
+        let element_class = self.next_token();
+        self.top_level = false;
+        let element_class = match element_class? {
             Some(ReadToken::String(string_token)) => string_token,
+            Some(_) if has_elements => {
+                return Err(KeyValues2SerializationError::TrailingContent(self.line, self.column));
+            }
             Some(ReadToken::OpenBrace) => {
                 return Err(KeyValues2SerializationError::UnexpectedOpenBrace(self.line, self.column));
             }
@@ -830,14 +1032,18 @@ impl<T: BufRead> StringReader<T> {
             }
             None => return Ok(None),
         };
+        let class_line = self.line;
+        let class_column = self.column.saturating_sub(element_class.len().saturating_sub(1));
 
-        let mut element = Element::new(element_class);
+        let mut element = Element::new(self.classes.intern(&element_class));
         if collected_elements.insert(*element.get_id(), Element::clone(&element)).is_some() {
             return Err(KeyValues2SerializationError::DuplicateGeneratedElementId);
         }
 
-        if !matches!(self.next_token()?, Some(ReadToken::OpenBrace)) {
-            return Err(KeyValues2SerializationError::ExpectedOpenBrace(self.line, self.column));
+        match self.next_token()? {
+            Some(ReadToken::OpenBrace) => {}
+            None if has_elements => return Err(KeyValues2SerializationError::TrailingContent(class_line, class_column)),
+            _ => return Err(KeyValues2SerializationError::ExpectedOpenBrace(self.line, self.column)),
         }
 
         self.read_attributes(&mut element, collected_elements, element_remap)?;
@@ -882,6 +1088,9 @@ impl<T: BufRead> StringReader<T> {
                 }
             };
 
+            let attribute_type_line = self.line;
+            let attribute_type_column = self.column.saturating_sub(attribute_type.len().saturating_sub(1));
+
             if attribute_name == "name" && attribute_type != "string" {
                 return Err(KeyValues2SerializationError::InvalidNameAttributeType(
                     self.line,
@@ -925,11 +1134,26 @@ impl<T: BufRead> StringReader<T> {
             }
 
             if let Some(attribute) = self.read_attribute_value(&attribute_type)? {
+                // Only the "id" attribute is the element's own id, any other `elementid` can reference an element by id.
+                if let AttributeValue::ObjectId(element_id) = attribute {
+                    element_remap
+                        .entry(Element::clone(element))
+                        .or_default()
+                        .push((attribute_name.clone(), ElementAttributeRemap::ObjectId(element_id)));
+                }
                 element.set_attribute(attribute_name, Attribute::new(attribute));
                 continue;
             }
 
             if let Some(array_attribute) = self.read_attribute_array(&attribute_type)? {
+                if let AttributeValue::ObjectIdArray(element_ids) = &array_attribute
+                    && !element_ids.is_empty()
+                {
+                    element_remap
+                        .entry(Element::clone(element))
+                        .or_default()
+                        .push((attribute_name.clone(), ElementAttributeRemap::ObjectIdArray(element_ids.clone())));
+                }
                 element.set_attribute(attribute_name, Attribute::new(array_attribute));
                 continue;
             }
@@ -1039,8 +1263,17 @@ impl<T: BufRead> StringReader<T> {
                 continue;
             }
 
-            if !matches!(self.next_token()?, Some(ReadToken::OpenBrace)) {
-                return Err(KeyValues2SerializationError::ExpectedOpenBrace(self.line, self.column));
+            // Any other type is the class of an inline element, so a value or an array instead of a brace means the type is unknown.
+            match self.next_token()? {
+                Some(ReadToken::OpenBrace) => {}
+                Some(ReadToken::String(_) | ReadToken::OpenBracket) => {
+                    return Err(KeyValues2SerializationError::UnknownAttributeType {
+                        token: attribute_type,
+                        line: attribute_type_line,
+                        column: attribute_type_column,
+                    });
+                }
+                _ => return Err(KeyValues2SerializationError::ExpectedOpenBrace(self.line, self.column)),
             }
 
             element.set_attribute(
@@ -1173,11 +1406,15 @@ impl<T: BufRead> StringReader<T> {
             }
             "bool" => {
                 let attribute_value = get_attribute_value!(self);
-                Some(AttributeValue::Boolean(
-                    attribute_value.parse::<u8>().map_err(|_| {
-                        KeyValues2SerializationError::ParseBooleanError(self.line, self.column.saturating_sub(attribute_value.len().saturating_sub(1)))
-                    })? != 0,
-                ))
+                Some(AttributeValue::Boolean(match attribute_value.as_str() {
+                    "true" if self.lenient => true,
+                    "false" if self.lenient => false,
+                    _ => {
+                        attribute_value.parse::<u8>().map_err(|_| {
+                            KeyValues2SerializationError::ParseBooleanError(self.line, self.column.saturating_sub(attribute_value.len().saturating_sub(1)))
+                        })? != 0
+                    }
+                }))
             }
             "string" => {
                 let attribute_value = get_attribute_value!(self);
@@ -1334,12 +1571,18 @@ impl<T: BufRead> StringReader<T> {
         collected_elements: &mut IndexMap<UUID, Element>,
         element_remap: &mut IndexMap<Element, Vec<(String, ElementAttributeRemap)>>,
     ) -> Result<Element, KeyValues2SerializationError> {
-        let mut element = Element::new(element_class);
+        if self.depth >= self.max_depth {
+            return Err(KeyValues2SerializationError::MaxDepthExceeded { depth: self.max_depth });
+        }
+
+        let mut element = Element::new(self.classes.intern(&element_class));
         if collected_elements.insert(*element.get_id(), Element::clone(&element)).is_some() {
             return Err(KeyValues2SerializationError::DuplicateGeneratedElementId);
         }
 
+        self.depth += 1;
         self.read_attributes(&mut element, collected_elements, element_remap)?;
+        self.depth -= 1;
 
         Ok(element)
     }
@@ -1348,6 +1591,10 @@ impl<T: BufRead> StringReader<T> {
 enum ElementAttributeRemap {
     Single(UUID),
     Array(Vec<(usize, UUID)>),
+    /// An `elementid` value that only becomes an element if the id is in the file.
+    ObjectId(UUID),
+    /// An `elementid_array` that only becomes an element array if every id is in the file.
+    ObjectIdArray(Vec<UUID>),
 }
 
 enum ReadToken {
@@ -1363,6 +1610,97 @@ enum ReadToken {
 /// Encodes the data in a ASCII text format.
 ///
 /// Versions are between 1 and 4.
+///
+/// The `elementid` type is the element's own id when it's the `"id"` attribute.
+/// Any other `elementid` attribute with the id of an element in the file references that element, and is read as [AttributeValue::Element].
+/// An `elementid_array` is read as [AttributeValue::ElementArray] when every id is an element in the file.
+/// Other ids stay [AttributeValue::ObjectId] and [AttributeValue::ObjectIdArray], which are written as `elementid`, so they read back the same.
+///
+/// # Example
+/// The references can be written to binary version 9, which can't store the deprecated [AttributeValue::ObjectId].
+/// ```
+/// use datamodel::{Header, Serializer, attribute::{AttributeType, UUID}, serializers::{BinarySerializer, KeyValues2Serializer}};
+///
+/// let mut file: &[u8] = b"<!-- dmx encoding keyvalues2 4 format dmx 22 -->
+/// \"DmElement\"
+/// {
+///     \"id\" \"elementid\" \"9b0c0fa8-34b4-4b7f-b5b9-6a4b3b38a5e4\"
+///     \"target\" \"elementid\" \"4f2a1d34-5e6c-4c1b-9b0a-7d2e3f4a5b6c\"
+///     \"targets\" \"elementid_array\" [ \"4f2a1d34-5e6c-4c1b-9b0a-7d2e3f4a5b6c\" ]
+///     \"child\" \"DmeModel\"
+///     {
+///         \"id\" \"elementid\" \"4f2a1d34-5e6c-4c1b-9b0a-7d2e3f4a5b6c\"
+///         \"name\" \"string\" \"child\"
+///     }
+/// }
+/// ";
+/// let (_, encoding, version) = Header::from_buffer(&mut file).unwrap();
+/// let root = KeyValues2Serializer::deserialize(&mut file, encoding, version).unwrap();
+/// assert_eq!(*root.get_id(), "9b0c0fa8-34b4-4b7f-b5b9-6a4b3b38a5e4".parse::<UUID>().unwrap());
+/// assert_eq!(root.get_attribute("target").unwrap().get_type(), AttributeType::Element);
+/// assert_eq!(root.get_attribute("targets").unwrap().get_type(), AttributeType::ElementArray);
+///
+/// let mut binary = Vec::new();
+/// BinarySerializer::serialize_version(&mut binary, &Header::default(), &root, 9).unwrap();
+/// let (_, read) = datamodel::deserialize_slice(&binary).unwrap();
+/// let target = read.get_attribute("target").unwrap().as_element().unwrap().clone().unwrap();
+/// let child = read.get_attribute("child").unwrap().as_element().unwrap().clone().unwrap();
+/// assert_eq!(*target.get_id(), *child.get_id());
+/// assert_eq!(target.name_owned().as_deref(), Some("child"));
+/// ```
+///
+/// Ids that aren't elements in the file round trip as UUIDs.
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::{Attribute, AttributeType, AttributeValue, UUID}, serializers::KeyValues2Serializer};
+///
+/// let mut root = Element::named("root");
+/// root.set_attribute("guid", Attribute::new(AttributeValue::ObjectId(UUID::from_u128(1))));
+/// let root_id = *root.get_id();
+/// root.set_attribute("guids", Attribute::new(AttributeValue::ObjectIdArray(vec![UUID::from_u128(2), root_id])));
+/// let mut file = Vec::new();
+/// KeyValues2Serializer::serialize(&mut file, &Header::default(), &root).unwrap();
+///
+/// let (_, read) = datamodel::deserialize_slice(&file).unwrap();
+/// assert_eq!(read.get_attribute("guid").unwrap().get_type(), AttributeType::ObjectId);
+/// assert_eq!(read.get_attribute("guid").unwrap().as_object_id(), Some(UUID::from_u128(1)));
+/// // Not every id is an element, so the array stays UUIDs too.
+/// let guids = read.get_attribute("guids").unwrap();
+/// assert_eq!(guids.get_value::<Vec<UUID>>().as_deref(), Some(&vec![UUID::from_u128(2), root_id]));
+/// ```
+///
+/// Blank lines and comments after the last element are ignored, anything else is an error at its position.
+/// ```
+/// use datamodel::{Header, Serializer, serializers::{KeyValues2SerializationError, KeyValues2Serializer}};
+///
+/// let mut file: &[u8] = b"<!-- dmx encoding keyvalues2 4 format dmx 22 -->
+/// \"DmElement\"
+/// {
+/// }
+///
+/// // The end.
+///   }
+/// ";
+/// let (_, encoding, version) = Header::from_buffer(&mut file).unwrap();
+/// let result = KeyValues2Serializer::deserialize(&mut file, encoding, version);
+/// assert!(matches!(result, Err(KeyValues2SerializationError::TrailingContent(7, 3))));
+/// ```
+///
+/// A type that isn't known is an error at the type, for single values and arrays.
+/// ```
+/// use datamodel::{Header, Serializer, serializers::{KeyValues2SerializationError, KeyValues2Serializer}};
+///
+/// let read = |attribute: &str| {
+///     let file = format!("<!-- dmx encoding keyvalues2 4 format dmx 22 -->\n\"DmElement\"\n{{\n{attribute}\n}}\n");
+///     let mut file = file.as_bytes();
+///     let (_, encoding, version) = Header::from_buffer(&mut file).unwrap();
+///     KeyValues2Serializer::deserialize(&mut file, encoding, version)
+/// };
+///
+/// let result = read("\"origin\" \"vec3\" \"0 0 0\"");
+/// assert!(matches!(result, Err(KeyValues2SerializationError::UnknownAttributeType { token, line: 4, column: 12 }) if token == "vec3"));
+/// let result = read("\"points\" \"vec3_array\" [ \"0 0 0\" ]");
+/// assert!(matches!(result, Err(KeyValues2SerializationError::UnknownAttributeType { token, line: 4, column: 12 }) if token == "vec3_array"));
+/// ```
 pub struct KeyValues2Serializer;
 
 impl Serializer for KeyValues2Serializer {
@@ -1377,11 +1715,203 @@ impl Serializer for KeyValues2Serializer {
     }
 
     fn serialize_version(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<(), Self::Error> {
+        Self::serialize_with_options(buffer, header, root, version, KeyValues2Options::default())
+    }
+
+    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
+        Self::deserialize_with_options(buffer, encoding, version, KeyValues2Options::default())
+    }
+}
+
+impl KeyValues2Serializer {
+    /// Decodes the buffer for the root element with [KeyValues2Options].
+    ///
+    /// # Example
+    /// Reading unquoted values with [KeyValues2Options::lenient].
+    /// ```
+    /// use datamodel::{Header, serializers::{KeyValues2Options, KeyValues2Serializer}};
+    ///
+    /// let mut file: &[u8] = b"<!-- dmx encoding keyvalues2 4 format dmx 22 -->
+    /// \"DmElement\"
+    /// {
+    ///     \"count\" \"int\" 42
+    ///     \"visible\" \"bool\" true
+    ///     \"scale\" \"float\" -1.5
+    /// }
+    /// ";
+    /// let (_, encoding, version) = Header::from_buffer(&mut file).unwrap();
+    /// let options = KeyValues2Options { lenient: true, ..Default::default() };
+    /// let root = KeyValues2Serializer::deserialize_with_options(&mut file, encoding, version, options).unwrap();
+    /// assert_eq!(root.get_attribute("count").unwrap().as_i32(), Some(42));
+    /// assert_eq!(root.get_attribute("visible").unwrap().as_bool(), Some(true));
+    /// assert_eq!(root.get_attribute("scale").unwrap().as_f32(), Some(-1.5));
+    /// ```
+    pub fn deserialize_with_options(
+        buffer: &mut impl BufRead,
+        encoding: String,
+        version: i32,
+        options: KeyValues2Options,
+    ) -> Result<Element, KeyValues2SerializationError> {
+        if encoding != Self::name() {
+            return Err(KeyValues2SerializationError::WrongEncoding);
+        }
+
         if version < 1 || version > Self::version() {
             return Err(KeyValues2SerializationError::InvalidEncodingVersion);
         }
 
-        let mut writer = StringWriter::new(buffer);
+        Self::read_top_level_elements(buffer, options, false)?
+            .0
+            .into_iter()
+            .next()
+            .ok_or(KeyValues2SerializationError::NoElements)
+    }
+
+    /// Decodes the buffer for every element at the top level of the file, in the order they are in the file.
+    ///
+    /// [Serializer::deserialize] only returns the first of them as the root.
+    /// References between the elements are resolved the same way, and the prefix element is left out.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Header, serializers::KeyValues2Serializer};
+    ///
+    /// let mut file: &[u8] = b"<!-- dmx encoding keyvalues2 4 format dmx 22 -->
+    /// \"DmElement\" { \"name\" \"string\" \"first\" }
+    /// \"DmElement\" { \"name\" \"string\" \"second\" }
+    /// ";
+    /// let (_, encoding, version) = Header::from_buffer(&mut file).unwrap();
+    /// let elements = KeyValues2Serializer::deserialize_all(&mut file, encoding, version).unwrap();
+    /// let names = elements.iter().map(|element| element.name_owned().unwrap()).collect::<Vec<_>>();
+    /// assert_eq!(names, ["first", "second"]);
+    /// ```
+    pub fn deserialize_all(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Vec<Element>, KeyValues2SerializationError> {
+        if encoding != Self::name() {
+            return Err(KeyValues2SerializationError::WrongEncoding);
+        }
+
+        if version < 1 || version > Self::version() {
+            return Err(KeyValues2SerializationError::InvalidEncodingVersion);
+        }
+
+        Ok(Self::read_top_level_elements(buffer, KeyValues2Options::default(), false)?.0)
+    }
+
+    /// Decodes the top level elements of one document in a stream of documents, see [crate::deserialize_all].
+    ///
+    /// Reading stops at a header line between top level elements, and its offset in the buffer is returned with the elements.
+    /// A header line inside a string or an element is part of the document.
+    pub(crate) fn deserialize_document(
+        buffer: &mut impl BufRead,
+        encoding: String,
+        version: i32,
+    ) -> Result<(Vec<Element>, Option<usize>), KeyValues2SerializationError> {
+        if encoding != Self::name() {
+            return Err(KeyValues2SerializationError::WrongEncoding);
+        }
+
+        if version < 1 || version > Self::version() {
+            return Err(KeyValues2SerializationError::InvalidEncodingVersion);
+        }
+
+        Self::read_top_level_elements(buffer, KeyValues2Options::default(), true)
+    }
+
+    fn read_top_level_elements(
+        buffer: &mut impl BufRead,
+        options: KeyValues2Options,
+        stop_at_header: bool,
+    ) -> Result<(Vec<Element>, Option<usize>), KeyValues2SerializationError> {
+        let mut reader = StringReader::new(buffer, options);
+        reader.stop_at_header = stop_at_header;
+        let mut collected_elements = IndexMap::new();
+        let mut element_remap = IndexMap::new();
+        let mut top_level_elements = Vec::new();
+
+        while let Some(top_level_element) = reader.read_element(&mut collected_elements, &mut element_remap)? {
+            if !top_level_element.get_class().eq("$prefix_element$") {
+                top_level_elements.push(top_level_element);
+            }
+        }
+
+        for (mut element, remapping) in element_remap {
+            for (attribute_name, attribute_remap) in remapping {
+                match attribute_remap {
+                    ElementAttributeRemap::Single(uuid) | ElementAttributeRemap::ObjectId(uuid) => {
+                        if let Some(reference_element) = collected_elements.get(&uuid) {
+                            element.set_attribute(attribute_name, Attribute::new(AttributeValue::Element(Some(Element::clone(reference_element)))));
+                        }
+                    }
+                    ElementAttributeRemap::Array(remaps) => {
+                        if let Some(mut remapped_array) = element.get_attribute(&attribute_name).and_then(|attr| match &*attr.get_inner() {
+                            AttributeValue::ElementArray(arr) => Some(arr.clone()),
+                            _ => None,
+                        }) {
+                            for (index, uuid) in remaps {
+                                if let Some(reference_element) = collected_elements.get(&uuid) {
+                                    remapped_array[index] = Some(Element::clone(reference_element));
+                                }
+                            }
+
+                            element.set_attribute(attribute_name, Attribute::new(AttributeValue::ElementArray(remapped_array)));
+                        }
+                    }
+                    ElementAttributeRemap::ObjectIdArray(uuids) => {
+                        if let Some(remapped_array) = uuids
+                            .iter()
+                            .map(|uuid| collected_elements.get(uuid).cloned().map(Some))
+                            .collect::<Option<Vec<_>>>()
+                        {
+                            element.set_attribute(attribute_name, Attribute::new(AttributeValue::ElementArray(remapped_array)));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok((top_level_elements, reader.next_header))
+    }
+
+    /// Encodes a root element to a buffer with the current version and [KeyValues2Options::compact] set.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, serializers::KeyValues2Serializer};
+    ///
+    /// let mut root = Element::named("root");
+    /// root.link("child", &Element::named("child"));
+    ///
+    /// let mut compact = Vec::new();
+    /// KeyValues2Serializer::serialize_compact(&mut compact, &Header::default(), &root).unwrap();
+    /// let mut indented = Vec::new();
+    /// KeyValues2Serializer::serialize(&mut indented, &Header::default(), &root).unwrap();
+    /// assert!(compact.len() < indented.len());
+    /// assert!(!compact.contains(&b'\t') && !compact.contains(&b'\r'));
+    ///
+    /// let (_, read) = datamodel::deserialize_slice(&compact).unwrap();
+    /// assert_eq!(read.content_hash(), root.content_hash());
+    /// ```
+    pub fn serialize_compact(buffer: &mut impl Write, header: &Header, root: &Element) -> Result<(), KeyValues2SerializationError> {
+        let options = KeyValues2Options {
+            compact: true,
+            ..Default::default()
+        };
+        Self::serialize_with_options(buffer, header, root, Self::version(), options)
+    }
+
+    /// Encodes a root element to a buffer with a selected version and [KeyValues2Options].
+    pub fn serialize_with_options(
+        buffer: &mut impl Write,
+        header: &Header,
+        root: &Element,
+        version: i32,
+        options: KeyValues2Options,
+    ) -> Result<(), KeyValues2SerializationError> {
+        if version < 1 || version > Self::version() {
+            return Err(KeyValues2SerializationError::InvalidEncodingVersion);
+        }
+
+        let mut writer = StringWriter::new(buffer, options);
         writer.write_header(&header.create_header(Self::name(), version))?;
 
         fn collect_elements(root: Element, elements: &mut IndexMap<Element, usize>) {
@@ -1436,59 +1966,6 @@ impl Serializer for KeyValues2Serializer {
 
         Ok(())
     }
-
-    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
-        if encoding != Self::name() {
-            return Err(KeyValues2SerializationError::WrongEncoding);
-        }
-
-        if version < 1 || version > Self::version() {
-            return Err(KeyValues2SerializationError::InvalidEncodingVersion);
-        }
-
-        let mut reader = StringReader::new(buffer);
-        let mut collected_elements = IndexMap::new();
-        let mut element_remap = IndexMap::new();
-        let mut root = None;
-
-        while let Some(root_element) = reader.read_element(&mut collected_elements, &mut element_remap)? {
-            if root.is_none() && !root_element.get_class().eq("$prefix_element$") {
-                root = Some(root_element);
-            }
-        }
-
-        for (mut element, remapping) in element_remap {
-            for (attribute_name, attribute_remap) in remapping {
-                match attribute_remap {
-                    ElementAttributeRemap::Single(uuid) => {
-                        if let Some(reference_element) = collected_elements.get(&uuid) {
-                            element.set_attribute(attribute_name, Attribute::new(AttributeValue::Element(Some(Element::clone(reference_element)))));
-                        }
-                    }
-                    ElementAttributeRemap::Array(remaps) => {
-                        if let Some(mut remapped_array) = element.get_attribute(&attribute_name).and_then(|attr| match &*attr.get_inner() {
-                            AttributeValue::ElementArray(arr) => Some(arr.clone()),
-                            _ => None,
-                        }) {
-                            for (index, uuid) in remaps {
-                                if let Some(reference_element) = collected_elements.get(&uuid) {
-                                    remapped_array[index] = Some(Element::clone(reference_element));
-                                }
-                            }
-
-                            element.set_attribute(attribute_name, Attribute::new(AttributeValue::ElementArray(remapped_array)));
-                        }
-                    }
-                }
-            }
-        }
-
-        if let Some(root_element) = root {
-            return Ok(root_element);
-        }
-
-        Err(KeyValues2SerializationError::NoElements)
-    }
 }
 
 /// Valve's KeyValues2 Flat encoding Serializer.
@@ -1510,11 +1987,46 @@ impl Serializer for KeyValues2FlatSerializer {
     }
 
     fn serialize_version(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<(), Self::Error> {
+        Self::serialize_with_options(buffer, header, root, version, KeyValues2Options::default())
+    }
+
+    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
+        Self::deserialize_with_options(buffer, encoding, version, KeyValues2Options::default())
+    }
+}
+
+impl KeyValues2FlatSerializer {
+    /// Decodes the buffer for the root element with [KeyValues2Options].
+    pub fn deserialize_with_options(
+        buffer: &mut impl BufRead,
+        encoding: String,
+        version: i32,
+        options: KeyValues2Options,
+    ) -> Result<Element, KeyValues2SerializationError> {
+        if encoding != Self::name() {
+            return Err(KeyValues2SerializationError::WrongEncoding);
+        }
+
         if version < 1 || version > Self::version() {
             return Err(KeyValues2SerializationError::InvalidEncodingVersion);
         }
 
-        let mut writer = StringWriter::new(buffer);
+        KeyValues2Serializer::deserialize_with_options(buffer, String::from(KeyValues2Serializer::name()), KeyValues2Serializer::version(), options)
+    }
+
+    /// Encodes a root element to a buffer with a selected version and [KeyValues2Options].
+    pub fn serialize_with_options(
+        buffer: &mut impl Write,
+        header: &Header,
+        root: &Element,
+        version: i32,
+        options: KeyValues2Options,
+    ) -> Result<(), KeyValues2SerializationError> {
+        if version < 1 || version > Self::version() {
+            return Err(KeyValues2SerializationError::InvalidEncodingVersion);
+        }
+
+        let mut writer = StringWriter::new(buffer, options);
         writer.write_header(&header.create_header(Self::name(), version))?;
 
         fn collect_elements(root: Element, elements: &mut IndexMap<Element, usize>) {
@@ -1569,16 +2081,4 @@ impl Serializer for KeyValues2FlatSerializer {
 
         Ok(())
     }
-
-    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
-        if encoding != Self::name() {
-            return Err(KeyValues2SerializationError::WrongEncoding);
-        }
-
-        if version < 1 || version > Self::version() {
-            return Err(KeyValues2SerializationError::InvalidEncodingVersion);
-        }
-
-        KeyValues2Serializer::deserialize(buffer, String::from(KeyValues2Serializer::name()), KeyValues2Serializer::version())
-    }
 }
@@ -1,10 +1,32 @@
 //! Support for Valve made dmx encoding formats.
 
 mod binary;
+pub use binary::ArrayLocation;
+pub use binary::BinaryOptions;
+pub use binary::BinaryRecovery;
 pub use binary::BinarySerializationError;
 pub use binary::BinarySerializer;
+pub use binary::LazyArray;
+pub use binary::ProgressUpdate;
+pub use binary::SerializeReport;
+pub use binary::StringTableMode;
 
 mod keyvalues2;
 pub use keyvalues2::KeyValues2FlatSerializer;
+pub use keyvalues2::KeyValues2Options;
 pub use keyvalues2::KeyValues2SerializationError;
 pub use keyvalues2::KeyValues2Serializer;
+pub use keyvalues2::WriteContext;
+
+mod xml;
+pub use xml::XmlFlatSerializer;
+pub use xml::XmlOptions;
+pub use xml::XmlSerializationError;
+pub use xml::XmlSerializer;
+
+mod tree;
+pub use tree::TreeSerializationError;
+pub use tree::TreeSerializer;
+
+mod non_finite;
+pub use non_finite::NonFiniteFloats;
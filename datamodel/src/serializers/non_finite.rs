@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+
+use crate::attribute::AttributeValue;
+
+/// How the text serializers write floats that are `NaN` or infinity.
+///
+/// Text can't store them in a way Valve's tools can read back, binary stores them as they are.
+/// Set with [KeyValues2Options::non_finite_floats](super::KeyValues2Options::non_finite_floats) and [XmlOptions::non_finite_floats](super::XmlOptions::non_finite_floats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFiniteFloats {
+    /// Write them as `NaN`, `inf`, or `-inf`.
+    #[default]
+    Write,
+    /// Return a `NonFiniteFloat` error with the name of the attribute.
+    Error,
+    /// Write them as 0.
+    Sanitize,
+}
+
+impl NonFiniteFloats {
+    /// Returns the value to write, or [None] if it has a non-finite float and they are an error.
+    pub(crate) fn apply(self, value: &AttributeValue) -> Option<Cow<'_, AttributeValue>> {
+        match self {
+            Self::Write => Some(Cow::Borrowed(value)),
+            Self::Error if has_non_finite_float(value) => None,
+            Self::Sanitize if has_non_finite_float(value) => {
+                let mut value = value.clone();
+                sanitize_non_finite_floats(&mut value);
+                Some(Cow::Owned(value))
+            }
+            Self::Error | Self::Sanitize => Some(Cow::Borrowed(value)),
+        }
+    }
+}
+
+fn has_non_finite_float(value: &AttributeValue) -> bool {
+    fn any_non_finite(values: &[f32]) -> bool {
+        values.iter().any(|value| !value.is_finite())
+    }
+
+    match value {
+        AttributeValue::Float(value) => any_non_finite(&[*value]),
+        AttributeValue::Vector2(value) => any_non_finite(&[value.x, value.y]),
+        AttributeValue::Vector3(value) => any_non_finite(&[value.x, value.y, value.z]),
+        AttributeValue::Vector4(value) => any_non_finite(&[value.x, value.y, value.z, value.w]),
+        AttributeValue::Angle(value) => any_non_finite(&[value.pitch, value.yaw, value.roll]),
+        AttributeValue::Quaternion(value) => any_non_finite(&[value.x, value.y, value.z, value.w]),
+        AttributeValue::Matrix(value) => value.0.iter().any(|row| any_non_finite(row)),
+        AttributeValue::FloatArray(values) => any_non_finite(values),
+        AttributeValue::Vector2Array(values) => values.iter().any(|value| any_non_finite(&[value.x, value.y])),
+        AttributeValue::Vector3Array(values) => values.iter().any(|value| any_non_finite(&[value.x, value.y, value.z])),
+        AttributeValue::Vector4Array(values) => values.iter().any(|value| any_non_finite(&[value.x, value.y, value.z, value.w])),
+        AttributeValue::AngleArray(values) => values.iter().any(|value| any_non_finite(&[value.pitch, value.yaw, value.roll])),
+        AttributeValue::QuaternionArray(values) => values.iter().any(|value| any_non_finite(&[value.x, value.y, value.z, value.w])),
+        AttributeValue::MatrixArray(values) => values.iter().any(|value| value.0.iter().any(|row| any_non_finite(row))),
+        _ => false,
+    }
+}
+
+fn sanitize_non_finite_floats(value: &mut AttributeValue) {
+    fn sanitize(value: &mut f32) {
+        if !value.is_finite() {
+            *value = 0.0;
+        }
+    }
+
+    match value {
+        AttributeValue::Float(value) => sanitize(value),
+        AttributeValue::Vector2(value) => [&mut value.x, &mut value.y].into_iter().for_each(sanitize),
+        AttributeValue::Vector3(value) => [&mut value.x, &mut value.y, &mut value.z].into_iter().for_each(sanitize),
+        AttributeValue::Vector4(value) => [&mut value.x, &mut value.y, &mut value.z, &mut value.w].into_iter().for_each(sanitize),
+        AttributeValue::Angle(value) => [&mut value.pitch, &mut value.yaw, &mut value.roll].into_iter().for_each(sanitize),
+        AttributeValue::Quaternion(value) => [&mut value.x, &mut value.y, &mut value.z, &mut value.w].into_iter().for_each(sanitize),
+        AttributeValue::Matrix(value) => value.0.iter_mut().flatten().for_each(sanitize),
+        AttributeValue::FloatArray(values) => values.iter_mut().for_each(sanitize),
+        AttributeValue::Vector2Array(values) => values.iter_mut().flat_map(|value| [&mut value.x, &mut value.y]).for_each(sanitize),
+        AttributeValue::Vector3Array(values) => values
+            .iter_mut()
+            .flat_map(|value| [&mut value.x, &mut value.y, &mut value.z])
+            .for_each(sanitize),
+        AttributeValue::Vector4Array(values) => values
+            .iter_mut()
+            .flat_map(|value| [&mut value.x, &mut value.y, &mut value.z, &mut value.w])
+            .for_each(sanitize),
+        AttributeValue::AngleArray(values) => values
+            .iter_mut()
+            .flat_map(|value| [&mut value.pitch, &mut value.yaw, &mut value.roll])
+            .for_each(sanitize),
+        AttributeValue::QuaternionArray(values) => values
+            .iter_mut()
+            .flat_map(|value| [&mut value.x, &mut value.y, &mut value.z, &mut value.w])
+            .for_each(sanitize),
+        AttributeValue::MatrixArray(values) => values.iter_mut().flat_map(|value| value.0.iter_mut().flatten()).for_each(sanitize),
+        _ => {}
+    }
+}
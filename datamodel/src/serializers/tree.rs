@@ -0,0 +1,147 @@
+use std::io::{BufRead, Error as IOError, Write};
+
+use indexmap::IndexSet;
+use thiserror::Error as ThisError;
+
+use crate::{
+    attribute::{AttributeType, AttributeValue},
+    element::Element,
+    serializing::{Header, Serializer},
+};
+
+/// An error returned by [TreeSerializer] from serializing or deserializing.
+#[derive(Debug, ThisError)]
+pub enum TreeSerializationError {
+    #[error("IO Error: {0}")]
+    Io(#[from] IOError),
+    #[error("Header Serializer Version Is Different")]
+    InvalidEncodingVersion,
+    #[error("Tree Encoding Can't Be Deserialized")]
+    Unsupported,
+}
+
+/// A serializer that writes an indented outline of the element graph for reading, like the `tree` command.
+///
+/// Each element is a line with its class, name and id, followed by a line for each attribute with its type and value from [Attribute::summary](crate::attribute::Attribute::summary).
+/// The "name" attribute is shown on the element line instead of its own line.
+/// Elements that were already written are only shown by their element line, so graphs with cycles end.
+/// The header isn't written and the output can't be deserialized, [Serializer::deserialize] returns [TreeSerializationError::Unsupported].
+/// The output isn't a DMX file, so [Serializer::file_extension] is `txt`.
+///
+/// Versions are only 1.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, UUID}, serializers::TreeSerializer};
+///
+/// let mut root = Element::full("DmElement", UUID::from_u128(1));
+/// root.set_attribute("name", String::from("root").into_attribute());
+/// let mut child = Element::full("DmeModel", UUID::from_u128(2));
+/// child.set_attribute("scale", 2.0f32.into_attribute());
+/// root.set_attribute("children", vec![Some(child.clone()), None].into_attribute());
+/// root.link("model", &child);
+///
+/// let mut buffer = Vec::new();
+/// TreeSerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+/// assert_eq!(
+///     String::from_utf8(buffer).unwrap(),
+///     "DmElement \"root\" 00000000-0000-0000-0000-000000000001
+/// ├── children: ElementArray[2]
+/// │   ├── [0] DmeModel 00000000-0000-0000-0000-000000000002
+/// │   │   └── scale: Float: 2.0
+/// │   └── [1] null
+/// └── model: Element: DmeModel 00000000-0000-0000-0000-000000000002 (see above)
+/// "
+/// );
+/// ```
+pub struct TreeSerializer;
+
+impl Serializer for TreeSerializer {
+    type Error = TreeSerializationError;
+
+    fn name() -> &'static str {
+        "tree"
+    }
+
+    fn version() -> i32 {
+        1
+    }
+
+    fn file_extension() -> &'static str {
+        "txt"
+    }
+
+    fn serialize_version(buffer: &mut impl Write, _header: &Header, root: &Element, version: i32) -> Result<(), Self::Error> {
+        if version < 1 || version > Self::version() {
+            return Err(TreeSerializationError::InvalidEncodingVersion);
+        }
+
+        let mut written = IndexSet::from([Element::clone(root)]);
+        writeln!(buffer, "{}", element_line(root))?;
+        write_attributes(buffer, root, "", &mut written)
+    }
+
+    fn deserialize(_buffer: &mut impl BufRead, _encoding: String, _version: i32) -> Result<Element, Self::Error> {
+        Err(TreeSerializationError::Unsupported)
+    }
+}
+
+fn element_line(element: &Element) -> String {
+    match element.name_owned() {
+        Some(name) => format!("{} \"{}\" {}", element.get_class(), name, element.get_id()),
+        None => format!("{} {}", element.get_class(), element.get_id()),
+    }
+}
+
+/// Writes the element line, then the attributes of the element if it wasn't written before.
+fn write_element(buffer: &mut impl Write, line: &str, element: &Element, prefix: &str, written: &mut IndexSet<Element>) -> Result<(), TreeSerializationError> {
+    if !written.insert(Element::clone(element)) {
+        writeln!(buffer, "{line}{} (see above)", element_line(element))?;
+        return Ok(());
+    }
+
+    writeln!(buffer, "{line}{}", element_line(element))?;
+    write_attributes(buffer, element, prefix, written)
+}
+
+fn write_attributes(buffer: &mut impl Write, element: &Element, prefix: &str, written: &mut IndexSet<Element>) -> Result<(), TreeSerializationError> {
+    let attributes = element
+        .get_attributes()
+        .iter()
+        .filter(|(attribute_name, attribute)| !(*attribute_name == "name" && attribute.get_type() == AttributeType::String))
+        .map(|(attribute_name, attribute)| (attribute_name.clone(), attribute.clone()))
+        .collect::<Vec<_>>();
+
+    for (attribute_index, (attribute_name, attribute)) in attributes.iter().enumerate() {
+        let (branch, indent) = if attribute_index + 1 == attributes.len() {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        let child_prefix = format!("{prefix}{indent}");
+
+        match &*attribute.get_inner() {
+            AttributeValue::Element(Some(child)) => {
+                write_element(buffer, &format!("{prefix}{branch}{attribute_name}: Element: "), child, &child_prefix, written)?;
+            }
+            AttributeValue::ElementArray(children) => {
+                writeln!(buffer, "{prefix}{branch}{attribute_name}: ElementArray[{}]", children.len())?;
+                for (child_index, child) in children.iter().enumerate() {
+                    let (child_branch, child_indent) = if child_index + 1 == children.len() {
+                        ("└── ", "    ")
+                    } else {
+                        ("├── ", "│   ")
+                    };
+                    let line = format!("{child_prefix}{child_branch}[{child_index}] ");
+                    match child {
+                        Some(child) => write_element(buffer, &line, child, &format!("{child_prefix}{child_indent}"), written)?,
+                        None => writeln!(buffer, "{line}null")?,
+                    }
+                }
+            }
+            _ => writeln!(buffer, "{prefix}{branch}{attribute_name}: {}", attribute.summary())?,
+        }
+    }
+
+    Ok(())
+}
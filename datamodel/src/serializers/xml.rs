@@ -0,0 +1,1295 @@
+use std::io::{BufRead, Error as IOError, Write};
+
+use indexmap::IndexMap;
+use thiserror::Error as ThisError;
+use uuid::Uuid as UUID;
+
+use crate::{
+    attribute::{Angle, Attribute, AttributeType, AttributeValue, BinaryBlock, Color, Matrix, Quaternion, Time, Vector2, Vector3, Vector4},
+    element::{ClassInterner, Element},
+    serializers::NonFiniteFloats,
+    serializing::{Header, Serializer},
+};
+
+/// An error returned by [XmlSerializer] and [XmlFlatSerializer] from serializing or deserializing.
+#[derive(Debug, ThisError)]
+pub enum XmlSerializationError {
+    #[error("IO Error: {0}")]
+    Io(#[from] IOError),
+    #[error("Header Serializer Is Different")]
+    WrongEncoding,
+    #[error("Header Serializer Version Is Different")]
+    InvalidEncodingVersion,
+    #[error("Unexpected Character \"{0}\" At {1},{2}")]
+    UnexpectedCharacter(char, usize, usize),
+    #[error("Unexpected End Of File")]
+    UnexpectedEndOfFile,
+    #[error("Unknown Entity \"{0}\" At {1},{2}")]
+    UnknownEntity(String, usize, usize),
+    #[error("Unexpected Tag \"{0}\" At {1},{2}")]
+    UnexpectedTag(String, usize, usize),
+    #[error("Tag Is Missing \"{0}\" At {1},{2}")]
+    MissingTagAttribute(&'static str, usize, usize),
+    #[error("Unknown Attribute Type \"{0}\" At {1},{2}")]
+    UnknownAttributeType(String, usize, usize),
+    #[error("Invalid Attribute Value At {0},{1}")]
+    InvalidAttributeValue(usize, usize),
+    #[error("Failed To Parse UUID At {0},{1}")]
+    ParseUUIDError(usize, usize),
+    #[error("Invalid Name Attribute Type At {0},{1}")]
+    InvalidNameAttributeType(usize, usize),
+    #[error("Attribute \"name\" In Element \"{}\" Is Not Type String", element.get_id())]
+    InvalidNameAttribute { element: Element },
+    #[error("Attribute \"{}\" In Element \"{}\" Has A Non-Finite Float", attribute, element.get_id())]
+    NonFiniteFloat { attribute: String, element: Element },
+    #[error("Element Id \"{0}\" Already Exists")]
+    DuplicateElementId(UUID),
+    #[error("No Elements In File")]
+    NoElements,
+    #[error("Inline Elements Are Nested Deeper Than The Max Depth {depth}")]
+    MaxDepthExceeded { depth: usize },
+}
+
+/// A value that can be written as the text of a `value` tag attribute.
+trait XmlValue: Sized {
+    fn to_xml(&self) -> String;
+    fn from_xml(value: &str) -> Option<Self>;
+}
+
+fn parse_floats<const N: usize>(value: &str) -> Option<[f32; N]> {
+    let mut values = [0.0; N];
+    let mut tokens = value.split_whitespace();
+
+    for value in &mut values {
+        *value = tokens.next()?.parse().ok()?;
+    }
+
+    if tokens.next().is_some() {
+        return None;
+    }
+
+    Some(values)
+}
+
+impl XmlValue for i32 {
+    fn to_xml(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        value.trim().parse().ok()
+    }
+}
+
+impl XmlValue for f32 {
+    fn to_xml(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        value.trim().parse().ok()
+    }
+}
+
+impl XmlValue for bool {
+    fn to_xml(&self) -> String {
+        (*self as u8).to_string()
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        value.trim().parse::<u8>().ok().map(|value| value != 0)
+    }
+}
+
+impl XmlValue for String {
+    fn to_xml(&self) -> String {
+        self.clone()
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        Some(value.to_string())
+    }
+}
+
+impl XmlValue for BinaryBlock {
+    fn to_xml(&self) -> String {
+        self.0.iter().fold(String::with_capacity(self.0.len() * 2), |mut output, byte| {
+            output.push_str(&format!("{byte:02X}"));
+            output
+        })
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let digits = value.chars().filter(|character| !character.is_whitespace()).collect::<Vec<_>>();
+
+        if digits.len() % 2 != 0 {
+            return None;
+        }
+
+        let mut block = BinaryBlock::default();
+        for byte in digits.chunks(2) {
+            block.0.push(u8::from_str_radix(&byte.iter().collect::<String>(), 16).ok()?);
+        }
+
+        Some(block)
+    }
+}
+
+impl XmlValue for UUID {
+    fn to_xml(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        value.trim().parse().ok()
+    }
+}
+
+impl XmlValue for Time {
+    fn to_xml(&self) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let tenths_of_milliseconds = self.0.unsigned_abs();
+        format!("{sign}{}.{:04}", tenths_of_milliseconds / 10000, tenths_of_milliseconds % 10000)
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let seconds = value.trim().parse::<f64>().ok()?;
+        let tenths_of_milliseconds = (seconds * 10000.0).round();
+
+        if tenths_of_milliseconds > i32::MAX as f64 || tenths_of_milliseconds < i32::MIN as f64 {
+            return None;
+        }
+
+        Some(Time(tenths_of_milliseconds as i32))
+    }
+}
+
+impl XmlValue for Color {
+    fn to_xml(&self) -> String {
+        format!("{} {} {} {}", self.red, self.green, self.blue, self.alpha)
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let mut tokens = value.split_whitespace();
+        let color = Color {
+            red: tokens.next()?.parse().ok()?,
+            green: tokens.next()?.parse().ok()?,
+            blue: tokens.next()?.parse().ok()?,
+            alpha: tokens.next()?.parse().ok()?,
+        };
+
+        if tokens.next().is_some() {
+            return None;
+        }
+
+        Some(color)
+    }
+}
+
+impl XmlValue for Vector2 {
+    fn to_xml(&self) -> String {
+        format!("{} {}", self.x, self.y)
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let [x, y] = parse_floats(value)?;
+        Some(Vector2 { x, y })
+    }
+}
+
+impl XmlValue for Vector3 {
+    fn to_xml(&self) -> String {
+        format!("{} {} {}", self.x, self.y, self.z)
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let [x, y, z] = parse_floats(value)?;
+        Some(Vector3 { x, y, z })
+    }
+}
+
+impl XmlValue for Vector4 {
+    fn to_xml(&self) -> String {
+        format!("{} {} {} {}", self.x, self.y, self.z, self.w)
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let [x, y, z, w] = parse_floats(value)?;
+        Some(Vector4 { x, y, z, w })
+    }
+}
+
+impl XmlValue for Angle {
+    fn to_xml(&self) -> String {
+        format!("{} {} {}", self.pitch, self.yaw, self.roll)
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let [pitch, yaw, roll] = parse_floats(value)?;
+        Some(Angle { pitch, yaw, roll })
+    }
+}
+
+impl XmlValue for Quaternion {
+    fn to_xml(&self) -> String {
+        format!("{} {} {} {}", self.x, self.y, self.z, self.w)
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let [x, y, z, w] = parse_floats(value)?;
+        Some(Quaternion { x, y, z, w })
+    }
+}
+
+impl XmlValue for Matrix {
+    fn to_xml(&self) -> String {
+        self.to_flat().iter().map(|value| value.to_string()).collect::<Vec<_>>().join(" ")
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        Some(Matrix::from_flat(&parse_floats(value)?))
+    }
+}
+
+impl XmlValue for u64 {
+    fn to_xml(&self) -> String {
+        format!("0x{self:01X}")
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        let value = value.trim();
+        u64::from_str_radix(value.strip_prefix("0x").unwrap_or(value), 16).ok()
+    }
+}
+
+impl XmlValue for u8 {
+    fn to_xml(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_xml(value: &str) -> Option<Self> {
+        value.trim().parse().ok()
+    }
+}
+
+fn escape_characters(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            '\t' => result.push_str("&#9;"),
+            '\n' => result.push_str("&#10;"),
+            '\r' => result.push_str("&#13;"),
+            _ => result.push(character),
+        }
+    }
+
+    result
+}
+
+/// Options that change how [XmlSerializer] and [XmlFlatSerializer] write a file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XmlOptions {
+    /// How `NaN` and infinite floats are written, [NonFiniteFloats::Error] returns [XmlSerializationError::NonFiniteFloat].
+    ///
+    /// Defaults to [NonFiniteFloats::Write], which writes them as before and can't be read back.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, Vector3}};
+    /// use datamodel::serializers::{NonFiniteFloats, XmlOptions, XmlSerializationError, XmlSerializer};
+    ///
+    /// let write = |root: &Element, non_finite_floats: NonFiniteFloats| {
+    ///     let options = XmlOptions { non_finite_floats };
+    ///     let mut buffer = Vec::new();
+    ///     XmlSerializer::serialize_with_options(&mut buffer, &Header::default(), root, XmlSerializer::version(), options).map(|_| buffer)
+    /// };
+    ///
+    /// let mut scalar = Element::named("scalar");
+    /// scalar.set_attribute("scale", f32::NAN.into_attribute());
+    /// let mut array = Element::named("array");
+    /// array.set_attribute("weights", vec![1.0f32, f32::INFINITY].into_attribute());
+    /// let mut vector = Element::named("vector");
+    /// vector.set_attribute("position", Vector3 { x: 1.0, y: f32::NEG_INFINITY, z: 3.0 }.into_attribute());
+    ///
+    /// for (root, attribute) in [(&scalar, "scale"), (&array, "weights"), (&vector, "position")] {
+    ///     // The default writes them like before.
+    ///     let mut buffer = Vec::new();
+    ///     XmlSerializer::serialize(&mut buffer, &Header::default(), root).unwrap();
+    ///     assert_eq!(buffer, write(root, NonFiniteFloats::Write).unwrap());
+    ///
+    ///     match write(root, NonFiniteFloats::Error) {
+    ///         Err(XmlSerializationError::NonFiniteFloat { attribute: name, element }) => {
+    ///             assert_eq!(name, attribute);
+    ///             assert_eq!(element, *root);
+    ///         }
+    ///         result => panic!("{result:?}"),
+    ///     }
+    /// }
+    ///
+    /// let (_, read) = datamodel::deserialize_slice(&write(&scalar, NonFiniteFloats::Sanitize).unwrap()).unwrap();
+    /// assert_eq!(read.get_copied::<f32>("scale"), Some(0.0));
+    /// let (_, read) = datamodel::deserialize_slice(&write(&array, NonFiniteFloats::Sanitize).unwrap()).unwrap();
+    /// assert_eq!(read.get_attribute("weights").unwrap().get_value::<Vec<f32>>().as_deref(), Some(&vec![1.0, 0.0]));
+    /// let (_, read) = datamodel::deserialize_slice(&write(&vector, NonFiniteFloats::Sanitize).unwrap()).unwrap();
+    /// let position = read.get_copied::<Vector3>("position").unwrap();
+    /// assert_eq!((position.x, position.y, position.z), (1.0, 0.0, 3.0));
+    /// ```
+    pub non_finite_floats: NonFiniteFloats,
+}
+
+struct XmlWriter<T: Write> {
+    buffer: T,
+    tab_index: usize,
+    options: XmlOptions,
+}
+
+impl<T: Write> XmlWriter<T> {
+    fn new(buffer: T, options: XmlOptions) -> Self {
+        Self { buffer, tab_index: 0, options }
+    }
+
+    fn write_header(&mut self, line: &str) -> Result<(), XmlSerializationError> {
+        self.buffer.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), XmlSerializationError> {
+        if self.tab_index > 0 {
+            self.buffer.write_all(&vec![b'\t'; self.tab_index])?;
+        }
+        self.buffer.write_all(line.as_bytes())?;
+        self.buffer.write_all(b"\r\n")?;
+        Ok(())
+    }
+
+    fn write_element(&mut self, element: &Element, collected_elements: &IndexMap<Element, usize>) -> Result<(), XmlSerializationError> {
+        let element_tag = format!("<element class=\"{}\" id=\"{}\"", escape_characters(&element.get_class()), element.get_id());
+
+        if element.get_attributes().is_empty() {
+            return self.write_line(&format!("{element_tag}/>"));
+        }
+
+        self.write_line(&format!("{element_tag}>"))?;
+        self.tab_index += 1;
+        self.write_attributes(element, collected_elements)?;
+        self.tab_index -= 1;
+        self.write_line("</element>")
+    }
+
+    fn write_attributes(&mut self, element: &Element, collected_elements: &IndexMap<Element, usize>) -> Result<(), XmlSerializationError> {
+        for (name, attribute) in element.get_attributes().iter() {
+            if name == "name" && attribute.get_type() != AttributeType::String {
+                return Err(XmlSerializationError::InvalidNameAttribute {
+                    element: Element::clone(element),
+                });
+            }
+
+            let attribute_tag = format!("<attribute name=\"{}\" type=\"{}\"", escape_characters(name), attribute.get_type().type_name());
+            let attribute_value = attribute.get_inner();
+            let Some(attribute_value) = self.options.non_finite_floats.apply(&attribute_value) else {
+                return Err(XmlSerializationError::NonFiniteFloat {
+                    attribute: name.clone(),
+                    element: Element::clone(element),
+                });
+            };
+
+            match &*attribute_value {
+                AttributeValue::Element(value) => match value {
+                    Some(value) if collected_elements.get(value).is_some_and(|&count| count == 0) => {
+                        self.write_line(&format!("{attribute_tag}>"))?;
+                        self.tab_index += 1;
+                        self.write_element(value, collected_elements)?;
+                        self.tab_index -= 1;
+                        self.write_line("</attribute>")?;
+                    }
+                    Some(value) => self.write_line(&format!("{attribute_tag} value=\"{}\"/>", value.get_id()))?,
+                    None => self.write_line(&format!("{attribute_tag} value=\"\"/>"))?,
+                },
+                AttributeValue::ElementArray(values) => {
+                    if values.is_empty() {
+                        self.write_line(&format!("{attribute_tag}/>"))?;
+                        continue;
+                    }
+
+                    self.write_line(&format!("{attribute_tag}>"))?;
+                    self.tab_index += 1;
+                    for value in values {
+                        match value {
+                            Some(value) if collected_elements.get(value).is_some_and(|&count| count == 0) => self.write_element(value, collected_elements)?,
+                            Some(value) => self.write_line(&format!("<item value=\"{}\"/>", value.get_id()))?,
+                            None => self.write_line("<item value=\"\"/>")?,
+                        }
+                    }
+                    self.tab_index -= 1;
+                    self.write_line("</attribute>")?;
+                }
+                value => self.write_value(&attribute_tag, value)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes an attribute that isn't an element, kept out of [XmlWriter::write_attributes] so nested elements use less stack.
+    fn write_value(&mut self, attribute_tag: &str, value: &AttributeValue) -> Result<(), XmlSerializationError> {
+        macro_rules! write_attribute_values {
+            ($($variant:ident),* $(,)?) => {
+                paste::paste! {
+                    match value {
+                        AttributeValue::Element(_) | AttributeValue::ElementArray(_) => unreachable!("elements are written by write_attributes"),
+                        $(
+                            AttributeValue::$variant(value) => {
+                                self.write_line(&format!("{attribute_tag} value=\"{}\"/>", escape_characters(&value.to_xml())))
+                            }
+                            AttributeValue::[<$variant Array>](values) => self.write_array(attribute_tag, values),
+                        )*
+                    }
+                }
+            };
+        }
+
+        write_attribute_values!(
+            Integer, Float, Boolean, String, Binary, ObjectId, Time, Color, Vector2, Vector3, Vector4, Angle, Quaternion, Matrix, ULong, UByte,
+        )
+    }
+
+    fn write_array<V: XmlValue>(&mut self, attribute_tag: &str, values: &[V]) -> Result<(), XmlSerializationError> {
+        if values.is_empty() {
+            return self.write_line(&format!("{attribute_tag}/>"));
+        }
+
+        self.write_line(&format!("{attribute_tag}>"))?;
+        self.tab_index += 1;
+        for value in values {
+            self.write_line(&format!("<item value=\"{}\"/>", escape_characters(&value.to_xml())))?;
+        }
+        self.tab_index -= 1;
+        self.write_line("</attribute>")
+    }
+}
+
+enum XmlToken {
+    Open {
+        tag: String,
+        attributes: Vec<(String, String)>,
+        empty: bool,
+    },
+    Close(String),
+}
+
+enum ElementAttributeRemap {
+    Single(UUID),
+    Array(Vec<(usize, UUID)>),
+}
+
+/// The deepest inline elements can be nested when reading before [XmlSerializationError::MaxDepthExceeded] is returned.
+const MAX_DEPTH: usize = 256;
+
+struct XmlReader {
+    text: String,
+    position: usize,
+    token_position: usize,
+    classes: ClassInterner,
+    depth: usize,
+}
+
+impl XmlReader {
+    fn new(buffer: &mut impl BufRead) -> Result<Self, XmlSerializationError> {
+        let mut text = String::new();
+        buffer.read_to_string(&mut text)?;
+        Ok(Self::from_text(text))
+    }
+
+    fn from_text(text: String) -> Self {
+        Self {
+            text,
+            position: 0,
+            token_position: 0,
+            classes: ClassInterner::default(),
+            depth: 0,
+        }
+    }
+
+    fn location(&self, position: usize) -> (usize, usize) {
+        let read_text = &self.text[..position];
+        let line = read_text.matches('\n').count() + 1;
+        let line_start = read_text.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        (line, read_text[line_start..].chars().count() + 1)
+    }
+
+    fn token_location(&self) -> (usize, usize) {
+        self.location(self.token_position)
+    }
+
+    fn remaining(&self) -> &str {
+        &self.text[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let remaining = self.remaining();
+        self.position += remaining.len() - remaining.trim_start().len();
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), XmlSerializationError> {
+        match self.remaining().find(end) {
+            Some(index) => {
+                self.position += index + end.len();
+                Ok(())
+            }
+            None => Err(XmlSerializationError::UnexpectedEndOfFile),
+        }
+    }
+
+    fn unexpected_character(&self) -> XmlSerializationError {
+        match self.remaining().chars().next() {
+            Some(character) => {
+                let (line, column) = self.location(self.position);
+                XmlSerializationError::UnexpectedCharacter(character, line, column)
+            }
+            None => XmlSerializationError::UnexpectedEndOfFile,
+        }
+    }
+
+    fn expect_character(&mut self, expected: char) -> Result<(), XmlSerializationError> {
+        if !self.remaining().starts_with(expected) {
+            return Err(self.unexpected_character());
+        }
+        self.position += expected.len_utf8();
+        Ok(())
+    }
+
+    fn read_name(&mut self) -> Result<String, XmlSerializationError> {
+        let remaining = self.remaining();
+        let length = remaining
+            .find(|character: char| character.is_whitespace() || "<>/=\"'".contains(character))
+            .unwrap_or(remaining.len());
+
+        if length == 0 {
+            return Err(self.unexpected_character());
+        }
+
+        let name = remaining[..length].to_string();
+        self.position += length;
+        Ok(name)
+    }
+
+    fn read_quoted(&mut self) -> Result<String, XmlSerializationError> {
+        let quote = match self.remaining().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.unexpected_character()),
+        };
+        self.position += 1;
+
+        let length = self.remaining().find(quote).ok_or(XmlSerializationError::UnexpectedEndOfFile)?;
+        let value_position = self.position;
+        self.position += length + 1;
+
+        let raw_value = &self.text[value_position..value_position + length];
+        let mut value = String::with_capacity(raw_value.len());
+        let mut rest = raw_value;
+
+        while let Some(index) = rest.find('&') {
+            value.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+
+            let entity_error = || {
+                let (line, column) = self.location(value_position);
+                XmlSerializationError::UnknownEntity(rest.split(';').next().unwrap_or_default().to_string(), line, column)
+            };
+
+            let end = rest.find(';').ok_or_else(entity_error)?;
+            let entity = &rest[..end];
+            let character = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = if let Some(hex) = entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else if let Some(decimal) = entity.strip_prefix('#') {
+                        decimal.parse::<u32>().ok()
+                    } else {
+                        None
+                    };
+                    code.and_then(char::from_u32).ok_or_else(entity_error)?
+                }
+            };
+
+            value.push(character);
+            rest = &rest[end + 1..];
+        }
+        value.push_str(rest);
+
+        Ok(value)
+    }
+
+    fn next_token(&mut self) -> Result<Option<XmlToken>, XmlSerializationError> {
+        loop {
+            self.skip_whitespace();
+            self.token_position = self.position;
+            let remaining = self.remaining();
+
+            if remaining.is_empty() {
+                return Ok(None);
+            }
+
+            if remaining.starts_with("<!--") {
+                self.skip_past("-->")?;
+                continue;
+            }
+
+            if remaining.starts_with("<?") {
+                self.skip_past("?>")?;
+                continue;
+            }
+
+            if remaining.starts_with("<!") {
+                self.skip_past(">")?;
+                continue;
+            }
+
+            if remaining.starts_with("</") {
+                self.position += 2;
+                let tag = self.read_name()?;
+                self.skip_whitespace();
+                self.expect_character('>')?;
+                return Ok(Some(XmlToken::Close(tag)));
+            }
+
+            if !remaining.starts_with('<') {
+                return Err(self.unexpected_character());
+            }
+
+            self.position += 1;
+            let tag = self.read_name()?;
+            let mut attributes = Vec::new();
+
+            loop {
+                self.skip_whitespace();
+
+                if self.remaining().starts_with("/>") {
+                    self.position += 2;
+                    return Ok(Some(XmlToken::Open { tag, attributes, empty: true }));
+                }
+
+                if self.remaining().starts_with('>') {
+                    self.position += 1;
+                    return Ok(Some(XmlToken::Open { tag, attributes, empty: false }));
+                }
+
+                let attribute_name = self.read_name()?;
+                self.skip_whitespace();
+                self.expect_character('=')?;
+                self.skip_whitespace();
+                let attribute_value = self.read_quoted()?;
+                attributes.push((attribute_name, attribute_value));
+            }
+        }
+    }
+
+    fn unexpected_tag(&self, token: Option<XmlToken>) -> XmlSerializationError {
+        let (line, column) = self.token_location();
+        match token {
+            Some(XmlToken::Open { tag, .. }) => XmlSerializationError::UnexpectedTag(tag, line, column),
+            Some(XmlToken::Close(tag)) => XmlSerializationError::UnexpectedTag(format!("/{tag}"), line, column),
+            None => XmlSerializationError::UnexpectedEndOfFile,
+        }
+    }
+
+    fn expect_close(&mut self, expected: &str) -> Result<(), XmlSerializationError> {
+        match self.next_token()? {
+            Some(XmlToken::Close(tag)) if tag == expected => Ok(()),
+            token => Err(self.unexpected_tag(token)),
+        }
+    }
+
+    fn tag_attribute<'a>(&self, attributes: &'a [(String, String)], name: &'static str) -> Result<&'a str, XmlSerializationError> {
+        attributes
+            .iter()
+            .find(|(attribute_name, _)| attribute_name == name)
+            .map(|(_, attribute_value)| attribute_value.as_str())
+            .ok_or_else(|| {
+                let (line, column) = self.token_location();
+                XmlSerializationError::MissingTagAttribute(name, line, column)
+            })
+    }
+
+    fn parse_value<V: XmlValue>(&self, value: &str) -> Result<V, XmlSerializationError> {
+        V::from_xml(value).ok_or_else(|| {
+            let (line, column) = self.token_location();
+            XmlSerializationError::InvalidAttributeValue(line, column)
+        })
+    }
+
+    fn parse_id(&self, value: &str) -> Result<UUID, XmlSerializationError> {
+        value.parse::<UUID>().map_err(|_| {
+            let (line, column) = self.token_location();
+            XmlSerializationError::ParseUUIDError(line, column)
+        })
+    }
+
+    fn read_document(&mut self) -> Result<Element, XmlSerializationError> {
+        match self.next_token()? {
+            Some(XmlToken::Open { tag, empty, .. }) if tag == "dmx" => {
+                if empty {
+                    return Err(XmlSerializationError::NoElements);
+                }
+            }
+            token => return Err(self.unexpected_tag(token)),
+        }
+
+        let mut collected_elements = IndexMap::new();
+        let mut element_remap = IndexMap::new();
+        let mut root = None;
+
+        loop {
+            match self.next_token()? {
+                Some(XmlToken::Open { tag, attributes, empty }) if tag == "element" => {
+                    let element = self.read_element(&attributes, empty, &mut collected_elements, &mut element_remap)?;
+                    if root.is_none() {
+                        root = Some(element);
+                    }
+                }
+                Some(XmlToken::Close(tag)) if tag == "dmx" => break,
+                token => return Err(self.unexpected_tag(token)),
+            }
+        }
+
+        for (mut element, remapping) in element_remap {
+            for (attribute_name, attribute_remap) in remapping {
+                match attribute_remap {
+                    ElementAttributeRemap::Single(uuid) => {
+                        if let Some(reference_element) = collected_elements.get(&uuid) {
+                            element.set_attribute(attribute_name, Attribute::new(AttributeValue::Element(Some(Element::clone(reference_element)))));
+                        }
+                    }
+                    ElementAttributeRemap::Array(remaps) => {
+                        if let Some(mut remapped_array) = element.get_attribute(&attribute_name).and_then(|attr| match &*attr.get_inner() {
+                            AttributeValue::ElementArray(arr) => Some(arr.clone()),
+                            _ => None,
+                        }) {
+                            for (index, uuid) in remaps {
+                                if let Some(reference_element) = collected_elements.get(&uuid) {
+                                    remapped_array[index] = Some(Element::clone(reference_element));
+                                }
+                            }
+
+                            element.set_attribute(attribute_name, Attribute::new(AttributeValue::ElementArray(remapped_array)));
+                        }
+                    }
+                }
+            }
+        }
+
+        root.ok_or(XmlSerializationError::NoElements)
+    }
+
+    fn read_element(
+        &mut self,
+        attributes: &[(String, String)],
+        empty: bool,
+        collected_elements: &mut IndexMap<UUID, Element>,
+        element_remap: &mut IndexMap<Element, Vec<(String, ElementAttributeRemap)>>,
+    ) -> Result<Element, XmlSerializationError> {
+        let element_class = self.tag_attribute(attributes, "class")?;
+        let element_id = self.parse_id(self.tag_attribute(attributes, "id")?)?;
+
+        if collected_elements.contains_key(&element_id) {
+            return Err(XmlSerializationError::DuplicateElementId(element_id));
+        }
+
+        let mut element = Element::full(self.classes.intern(element_class), element_id);
+        collected_elements.insert(element_id, Element::clone(&element));
+
+        if empty {
+            return Ok(element);
+        }
+
+        if self.depth >= MAX_DEPTH {
+            return Err(XmlSerializationError::MaxDepthExceeded { depth: MAX_DEPTH });
+        }
+
+        self.depth += 1;
+        loop {
+            match self.next_token()? {
+                Some(XmlToken::Open { tag, attributes, empty }) if tag == "attribute" => {
+                    self.read_attribute(&mut element, &attributes, empty, collected_elements, element_remap)?
+                }
+                Some(XmlToken::Close(tag)) if tag == "element" => break,
+                token => return Err(self.unexpected_tag(token)),
+            }
+        }
+        self.depth -= 1;
+
+        Ok(element)
+    }
+
+    fn read_attribute(
+        &mut self,
+        element: &mut Element,
+        attributes: &[(String, String)],
+        empty: bool,
+        collected_elements: &mut IndexMap<UUID, Element>,
+        element_remap: &mut IndexMap<Element, Vec<(String, ElementAttributeRemap)>>,
+    ) -> Result<(), XmlSerializationError> {
+        let attribute_name = self.tag_attribute(attributes, "name")?.to_string();
+        let attribute_type_name = self.tag_attribute(attributes, "type")?;
+        let attribute_type = AttributeType::from_type_name(attribute_type_name).ok_or_else(|| {
+            let (line, column) = self.token_location();
+            XmlSerializationError::UnknownAttributeType(attribute_type_name.to_string(), line, column)
+        })?;
+
+        if attribute_name == "name" && attribute_type != AttributeType::String {
+            let (line, column) = self.token_location();
+            return Err(XmlSerializationError::InvalidNameAttributeType(line, column));
+        }
+
+        let attribute_value = match attribute_type {
+            AttributeType::Element => self.read_element_value(element, &attribute_name, attributes, empty, collected_elements, element_remap)?,
+            AttributeType::ElementArray => self.read_element_array_value(element, &attribute_name, empty, collected_elements, element_remap)?,
+            _ => self.read_value(attribute_type, attributes, empty)?,
+        };
+
+        element.set_attribute(attribute_name, Attribute::new(attribute_value));
+
+        Ok(())
+    }
+
+    /// Reads a value that isn't an element, kept out of [XmlReader::read_attribute] so nested elements don't grow its stack frame.
+    fn read_value(&mut self, attribute_type: AttributeType, attributes: &[(String, String)], empty: bool) -> Result<AttributeValue, XmlSerializationError> {
+        macro_rules! read_attribute_values {
+            ($($variant:ident),* $(,)?) => {
+                paste::paste! {
+                    match attribute_type {
+                        AttributeType::Element | AttributeType::ElementArray => unreachable!("elements are read by read_attribute"),
+                        $(
+                            AttributeType::$variant => {
+                                let value = self.parse_value(self.tag_attribute(attributes, "value")?)?;
+                                if !empty {
+                                    self.expect_close("attribute")?;
+                                }
+                                AttributeValue::$variant(value)
+                            }
+                            AttributeType::[<$variant Array>] => AttributeValue::[<$variant Array>](self.read_items(empty)?),
+                        )*
+                    }
+                }
+            };
+        }
+
+        Ok(read_attribute_values!(
+            Integer, Float, Boolean, String, Binary, ObjectId, Time, Color, Vector2, Vector3, Vector4, Angle, Quaternion, Matrix, ULong, UByte,
+        ))
+    }
+
+    fn read_element_value(
+        &mut self,
+        element: &Element,
+        attribute_name: &str,
+        attributes: &[(String, String)],
+        empty: bool,
+        collected_elements: &mut IndexMap<UUID, Element>,
+        element_remap: &mut IndexMap<Element, Vec<(String, ElementAttributeRemap)>>,
+    ) -> Result<AttributeValue, XmlSerializationError> {
+        if let Ok(attribute_value) = self.tag_attribute(attributes, "value") {
+            if !attribute_value.is_empty() {
+                let element_id = self.parse_id(attribute_value)?;
+                element_remap
+                    .entry(Element::clone(element))
+                    .or_default()
+                    .push((attribute_name.to_string(), ElementAttributeRemap::Single(element_id)));
+            }
+
+            if !empty {
+                self.expect_close("attribute")?;
+            }
+
+            return Ok(AttributeValue::Element(None));
+        }
+
+        if empty {
+            return Ok(AttributeValue::Element(None));
+        }
+
+        match self.next_token()? {
+            Some(XmlToken::Open { tag, attributes, empty }) if tag == "element" => {
+                let child = self.read_element(&attributes, empty, collected_elements, element_remap)?;
+                self.expect_close("attribute")?;
+                Ok(AttributeValue::Element(Some(child)))
+            }
+            Some(XmlToken::Close(tag)) if tag == "attribute" => Ok(AttributeValue::Element(None)),
+            token => Err(self.unexpected_tag(token)),
+        }
+    }
+
+    fn read_element_array_value(
+        &mut self,
+        element: &Element,
+        attribute_name: &str,
+        empty: bool,
+        collected_elements: &mut IndexMap<UUID, Element>,
+        element_remap: &mut IndexMap<Element, Vec<(String, ElementAttributeRemap)>>,
+    ) -> Result<AttributeValue, XmlSerializationError> {
+        let mut elements = Vec::new();
+        let mut remaps = Vec::new();
+
+        if empty {
+            return Ok(AttributeValue::ElementArray(elements));
+        }
+
+        loop {
+            match self.next_token()? {
+                Some(XmlToken::Open { tag, attributes, empty }) if tag == "element" => {
+                    elements.push(Some(self.read_element(&attributes, empty, collected_elements, element_remap)?));
+                }
+                Some(XmlToken::Open { tag, attributes, empty }) if tag == "item" => {
+                    let item_value = self.tag_attribute(&attributes, "value")?;
+                    if !item_value.is_empty() {
+                        remaps.push((elements.len(), self.parse_id(item_value)?));
+                    }
+                    elements.push(None);
+
+                    if !empty {
+                        self.expect_close("item")?;
+                    }
+                }
+                Some(XmlToken::Close(tag)) if tag == "attribute" => break,
+                token => return Err(self.unexpected_tag(token)),
+            }
+        }
+
+        if !remaps.is_empty() {
+            element_remap
+                .entry(Element::clone(element))
+                .or_default()
+                .push((attribute_name.to_string(), ElementAttributeRemap::Array(remaps)));
+        }
+
+        Ok(AttributeValue::ElementArray(elements))
+    }
+
+    fn read_items<V: XmlValue>(&mut self, empty: bool) -> Result<Vec<V>, XmlSerializationError> {
+        let mut values = Vec::new();
+
+        if empty {
+            return Ok(values);
+        }
+
+        loop {
+            match self.next_token()? {
+                Some(XmlToken::Open { tag, attributes, empty }) if tag == "item" => {
+                    values.push(self.parse_value(self.tag_attribute(&attributes, "value")?)?);
+
+                    if !empty {
+                        self.expect_close("item")?;
+                    }
+                }
+                Some(XmlToken::Close(tag)) if tag == "attribute" => break,
+                token => return Err(self.unexpected_tag(token)),
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// Counts the references to each element, returning [XmlSerializationError::MaxDepthExceeded] for chains deeper than [MAX_DEPTH].
+fn collect_elements(root: Element, elements: &mut IndexMap<Element, usize>, flat: bool, depth: usize) -> Result<(), XmlSerializationError> {
+    if depth >= MAX_DEPTH {
+        return Err(XmlSerializationError::MaxDepthExceeded { depth: MAX_DEPTH });
+    }
+
+    elements.insert(root.clone(), if flat || elements.is_empty() { 1 } else { 0 });
+
+    for attribute in root.get_attributes().values() {
+        match &*attribute.get_inner() {
+            AttributeValue::Element(Some(element)) => {
+                if let Some(count) = elements.get_mut(element) {
+                    *count += 1;
+                    continue;
+                }
+                collect_elements(element.clone(), elements, flat, depth + 1)?;
+            }
+            AttributeValue::ElementArray(values) => {
+                for element in values.iter().flatten() {
+                    if let Some(count) = elements.get_mut(element) {
+                        *count += 1;
+                        continue;
+                    }
+                    collect_elements(element.clone(), elements, flat, depth + 1)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn serialize_elements(
+    buffer: &mut impl Write,
+    header: &Header,
+    root: &Element,
+    encoding: &str,
+    version: i32,
+    flat: bool,
+    options: XmlOptions,
+) -> Result<(), XmlSerializationError> {
+    let mut writer = XmlWriter::new(buffer, options);
+    writer.write_header(&header.create_header(encoding, version))?;
+
+    let mut collected_elements = IndexMap::new();
+    collect_elements(root.clone(), &mut collected_elements, flat, 0)?;
+
+    writer.write_line("<dmx>")?;
+    writer.tab_index += 1;
+    for (element, &use_count) in &collected_elements {
+        if use_count == 0 {
+            continue;
+        }
+
+        writer.write_element(element, &collected_elements)?;
+    }
+    writer.tab_index -= 1;
+    writer.write_line("</dmx>")?;
+
+    Ok(())
+}
+
+/// Valve's XML encoding Serializer.
+///
+/// Encodes the data as XML with `<element>` tags holding `<attribute>` tags.
+/// Elements referenced once are written inside the attribute, others are referenced by id.
+/// Reading returns [XmlSerializationError::MaxDepthExceeded] if inline elements are nested deeper than 256,
+/// and writing returns it if a chain of references from the root is deeper than 256.
+///
+/// Versions are only 1.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::{AttributeInfo, Vector3}, serializers::XmlSerializer};
+///
+/// let mut root = Element::named("root");
+/// root.set_attribute("position", Vector3 { x: 1.0, y: 2.0, z: 3.0 }.into_attribute());
+/// root.set_attribute("title", String::from("<Tom & \"Jerry\">").into_attribute());
+/// root.set_attribute("counts", vec![1, 2, 3].into_attribute());
+/// let shared = Element::named("shared");
+/// root.set_attribute("first", Some(shared.clone()).into_attribute());
+/// root.set_attribute("second", Some(shared.clone()).into_attribute());
+///
+/// let mut buffer = Vec::new();
+/// XmlSerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+/// // The shared element is referenced twice, so it's written after the root and referenced by id.
+/// assert!(String::from_utf8_lossy(&buffer).contains(&format!("value=\"{}\"", shared.get_id())));
+///
+/// let (_, read) = datamodel::deserialize(&mut buffer.as_slice()).unwrap();
+/// assert_eq!(*read.get_id(), *root.get_id());
+/// assert_eq!(read.get_copied::<Vector3>("position").map(|position| (position.x, position.y, position.z)), Some((1.0, 2.0, 3.0)));
+/// assert_eq!(read.get_attribute("title").unwrap().as_str().as_deref(), Some("<Tom & \"Jerry\">"));
+/// assert_eq!(read.get_attribute("counts").unwrap().get_value::<Vec<i32>>().as_deref(), Some(&vec![1, 2, 3]));
+/// let first = read.get_attribute("first").unwrap().as_element().unwrap().clone().unwrap();
+/// let second = read.get_attribute("second").unwrap().as_element().unwrap().clone().unwrap();
+/// assert_eq!(*first.get_id(), *shared.get_id());
+/// assert_eq!(first.name_owned().as_deref(), Some("shared"));
+/// assert_eq!(first, second);
+/// ```
+///
+/// Inline elements nested too deeply return an error instead of overflowing the stack.
+/// ```
+/// use datamodel::{Header, Serializer, serializers::{XmlSerializationError, XmlSerializer}};
+///
+/// let mut file = String::from("<!-- dmx encoding xml 1 format dmx 1 -->\n<dmx>\n");
+/// for index in 0..300 {
+///     file.push_str(&format!("<element class=\"DmElement\" id=\"00000000-0000-0000-0000-{index:012}\"><attribute name=\"child\" type=\"element\">"));
+/// }
+/// let mut file = file.as_bytes();
+/// let (_, encoding, version) = Header::from_buffer(&mut file).unwrap();
+/// let error = XmlSerializer::deserialize(&mut file, encoding, version).unwrap_err();
+/// assert!(matches!(error, XmlSerializationError::MaxDepthExceeded { depth: 256 }));
+/// ```
+///
+/// Writing has the same limit, so everything it writes can be read back.
+/// ```
+/// use datamodel::{Element, Header, Serializer, serializers::{XmlSerializationError, XmlSerializer}};
+///
+/// let chain = |length: usize| {
+///     let root = Element::named("root");
+///     let mut last = root.clone();
+///     for _ in 1..length {
+///         let next = Element::named("child");
+///         last.link("child", &next);
+///         last = next;
+///     }
+///     root
+/// };
+///
+/// let written = std::thread::Builder::new()
+///     .stack_size(2 * 1024 * 1024)
+///     .spawn(move || {
+///         let mut buffer = Vec::new();
+///         XmlSerializer::serialize(&mut buffer, &Header::default(), &chain(256)).unwrap();
+///         let readable = datamodel::deserialize_slice(&buffer).is_ok();
+///         let error = XmlSerializer::serialize(&mut Vec::new(), &Header::default(), &chain(300)).unwrap_err();
+///         readable && matches!(error, XmlSerializationError::MaxDepthExceeded { depth: 256 })
+///     })
+///     .unwrap()
+///     .join()
+///     .unwrap();
+/// assert!(written);
+/// ```
+pub struct XmlSerializer;
+
+impl Serializer for XmlSerializer {
+    type Error = XmlSerializationError;
+
+    fn name() -> &'static str {
+        "xml"
+    }
+
+    fn version() -> i32 {
+        1
+    }
+
+    fn serialize_version(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<(), Self::Error> {
+        Self::serialize_with_options(buffer, header, root, version, XmlOptions::default())
+    }
+
+    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
+        if encoding != Self::name() {
+            return Err(XmlSerializationError::WrongEncoding);
+        }
+
+        if version < 1 || version > Self::version() {
+            return Err(XmlSerializationError::InvalidEncodingVersion);
+        }
+
+        XmlReader::new(buffer)?.read_document()
+    }
+}
+
+impl XmlSerializer {
+    /// Encodes a root element to a buffer with a selected version and [XmlOptions].
+    pub fn serialize_with_options(
+        buffer: &mut impl Write,
+        header: &Header,
+        root: &Element,
+        version: i32,
+        options: XmlOptions,
+    ) -> Result<(), XmlSerializationError> {
+        if version < 1 || version > Self::version() {
+            return Err(XmlSerializationError::InvalidEncodingVersion);
+        }
+
+        serialize_elements(buffer, header, root, Self::name(), version, false, options)
+    }
+
+    /// Decodes one document at the start of a stream of documents, see [crate::deserialize_all].
+    ///
+    /// Returns the root and the length of the document, which ends after the closing `</dmx>` tag.
+    /// The buffer is read to the end, but only the text before the first byte that isn't UTF-8 is parsed, so the data can be followed by a binary document.
+    pub(crate) fn deserialize_document(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<(Element, usize), XmlSerializationError> {
+        if encoding != Self::name() && encoding != XmlFlatSerializer::name() {
+            return Err(XmlSerializationError::WrongEncoding);
+        }
+
+        if version < 1 || version > Self::version() {
+            return Err(XmlSerializationError::InvalidEncodingVersion);
+        }
+
+        let mut data = Vec::new();
+        buffer.read_to_end(&mut data)?;
+        let text = match std::str::from_utf8(&data) {
+            Ok(text) => text,
+            Err(error) => std::str::from_utf8(&data[..error.valid_up_to()]).unwrap_or_default(),
+        };
+        let mut reader = XmlReader::from_text(text.to_string());
+        let root = reader.read_document()?;
+        Ok((root, reader.position))
+    }
+}
+
+/// Valve's XML Flat encoding Serializer.
+///
+/// This is the same as [XmlSerializer] but no elements are inlined.
+///
+/// Versions are only 1.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::{Attribute, AttributeInfo}, serializers::XmlFlatSerializer};
+///
+/// let mut root = Element::named("root");
+/// let mut child = Element::named("child");
+/// child.set_attribute("scale", 2.5f32.into_attribute());
+/// child.set_attribute("parent", Some(root.clone()).into_attribute());
+/// root.set_attribute("children", Attribute::nullable_element_array([Some(child.clone()), None]));
+///
+/// let mut buffer = Vec::new();
+/// XmlFlatSerializer::serialize(&mut buffer, &Header::default(), &root).unwrap();
+/// // Every element is written at the top level and referenced by id.
+/// assert!(String::from_utf8_lossy(&buffer).contains(&format!("<item value=\"{}\"/>", child.get_id())));
+///
+/// let (_, read) = datamodel::deserialize(&mut buffer.as_slice()).unwrap();
+/// let children = read.get_attribute("children").unwrap().as_element_array().unwrap().to_vec();
+/// assert_eq!(children.len(), 2);
+/// let read_child = children[0].clone().unwrap();
+/// assert_eq!(*read_child.get_id(), *child.get_id());
+/// assert_eq!(read_child.get_copied::<f32>("scale"), Some(2.5));
+/// let parent = read_child.get_attribute("parent").unwrap().as_element().unwrap().clone().unwrap();
+/// assert_eq!(parent, read);
+/// assert!(children[1].is_none());
+/// ```
+pub struct XmlFlatSerializer;
+
+impl Serializer for XmlFlatSerializer {
+    type Error = XmlSerializationError;
+
+    fn name() -> &'static str {
+        "xml_flat"
+    }
+
+    fn version() -> i32 {
+        1
+    }
+
+    fn serialize_version(buffer: &mut impl Write, header: &Header, root: &Element, version: i32) -> Result<(), Self::Error> {
+        Self::serialize_with_options(buffer, header, root, version, XmlOptions::default())
+    }
+
+    fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error> {
+        if encoding != Self::name() {
+            return Err(XmlSerializationError::WrongEncoding);
+        }
+
+        if version < 1 || version > Self::version() {
+            return Err(XmlSerializationError::InvalidEncodingVersion);
+        }
+
+        XmlSerializer::deserialize(buffer, String::from(XmlSerializer::name()), XmlSerializer::version())
+    }
+}
+
+impl XmlFlatSerializer {
+    /// Encodes a root element to a buffer with a selected version and [XmlOptions].
+    pub fn serialize_with_options(
+        buffer: &mut impl Write,
+        header: &Header,
+        root: &Element,
+        version: i32,
+        options: XmlOptions,
+    ) -> Result<(), XmlSerializationError> {
+        if version < 1 || version > Self::version() {
+            return Err(XmlSerializationError::InvalidEncodingVersion);
+        }
+
+        serialize_elements(buffer, header, root, Self::name(), version, true, options)
+    }
+}
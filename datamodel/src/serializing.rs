@@ -1,13 +1,23 @@
 use std::{
-    io::{BufRead, Error, Write},
+    io::{BufRead, Cursor, Error, Read, Write},
     num::ParseIntError,
 };
 
+#[cfg(feature = "gzip")]
+use flate2::bufread::GzDecoder;
+#[cfg(feature = "gzip")]
+use std::io::BufReader;
 use thiserror::Error as ThisError;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 use crate::{
+    attribute::AttributeType,
     element::Element,
-    serializers::{BinarySerializationError, BinarySerializer, KeyValues2FlatSerializer, KeyValues2SerializationError, KeyValues2Serializer},
+    serializers::{
+        BinarySerializationError, BinarySerializer, KeyValues2FlatSerializer, KeyValues2SerializationError, KeyValues2Serializer, XmlFlatSerializer,
+        XmlSerializationError, XmlSerializer,
+    },
 };
 
 /// An error returned by [Header] when parsing a header.
@@ -25,25 +35,60 @@ pub enum FileHeaderError {
 
 const CURRENT_ENCODING: &str = "dmx";
 const CURRENT_FORMAT_VERSION: i32 = 22;
+const LEGACY_HEADER_START: &str = "<!-- DMXVersion ";
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
 
 /// The data stored in the header data of the DMX file.
 ///
 /// The header stores what the data represents in the file.
 ///
 /// The header must be at the beginning of the file.
+///
+/// # Compatibility
+/// The header keeps what was read from a file in private fields, so it can't be built with a struct literal like
+/// `Header { format, format_version }` anymore. Use [Header::new] or [Header::default] instead.
 #[derive(Debug, Clone)]
 pub struct Header {
     /// The identifier of what the file data represents for example "model" or "sfm".
     pub format: String,
     /// The numerical valve of the version that the file is representing.
+    ///
+    /// This is always the version of the format, it has no special values.
+    /// Legacy headers don't store a format version, so they are given the current "dmx" version, or 1 for "sfm" files.
+    /// Use [Header::is_legacy] to know if the header was legacy.
     pub format_version: i32,
+    raw: Option<String>,
+    encoding: Option<String>,
+    verbatim: bool,
+    style: HeaderStyle,
+}
+
+/// If a [Header] was read from a legacy `<!-- DMXVersion -->` header, see [Header::is_legacy].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum HeaderStyle {
+    #[default]
+    Modern,
+    Legacy,
+}
+
+/// Headers are equal when the format and format version are, [Header::raw] and [Header::set_verbatim] are only how the header is written and aren't compared.
+impl PartialEq for Header {
+    fn eq(&self, other: &Self) -> bool {
+        self.format == other.format && self.format_version == other.format_version
+    }
 }
 
+impl Eq for Header {}
+
 impl Default for Header {
     fn default() -> Self {
         Self {
             format: String::from(CURRENT_ENCODING),
             format_version: CURRENT_FORMAT_VERSION,
+            raw: None,
+            encoding: None,
+            verbatim: false,
+            style: HeaderStyle::Modern,
         }
     }
 }
@@ -52,19 +97,80 @@ impl Header {
     /// A way to create a new [Header] with specified format identifier and version.
     pub fn new(format: impl Into<String>, format_version: i32) -> Self {
         let format = format.into();
-        Self { format, format_version }
+        Self {
+            format,
+            format_version,
+            raw: None,
+            encoding: None,
+            verbatim: false,
+            style: HeaderStyle::Modern,
+        }
+    }
+
+    /// Returns the header line exactly as it was read, set by [Header::from_string] and [Header::from_buffer].
+    pub fn raw(&self) -> Option<&str> {
+        self.raw.as_deref()
+    }
+
+    /// Forgets the header line that was read.
+    pub fn clear_raw(&mut self) {
+        self.raw = None;
+    }
+
+    /// Sets if [Header::create_header] writes [Header::raw] back unchanged, so re-saving a file keeps its original header bytes.
+    ///
+    /// The line is only reused while it still describes the header being written, otherwise a new header is generated.
+    /// This is off by default.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Header;
+    ///
+    /// let line = "<!-- dmx encoding binary  9 format model 22 -->";
+    /// let (mut header, encoding, version) = Header::from_string(String::from(line)).unwrap();
+    /// assert_eq!(header.raw(), Some(line));
+    /// assert_eq!(header.create_header(&encoding, version), "<!-- dmx encoding binary 9 format model 22 -->\n");
+    ///
+    /// header.set_verbatim(true);
+    /// assert_eq!(header.create_header(&encoding, version), format!("{line}\n"));
+    /// // A different encoding version doesn't match the line, so a new header is written.
+    /// assert_eq!(header.create_header(&encoding, 5), "<!-- dmx encoding binary 5 format model 22 -->\n");
+    /// ```
+    pub fn set_verbatim(&mut self, verbatim: bool) {
+        self.verbatim = verbatim;
     }
 
     /// Parses a [Header] from a string.
     ///
+    /// Legacy `<!-- DMXVersion -->` headers are read as encoding version 1 of the "dmx" format, or of the "sfm" format for `sfm_vN`.
+    ///
     /// # Returns
     /// The [Header], encoding string, and encoding version that was parsed.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Header;
+    ///
+    /// let legacy = |encoding: &str| {
+    ///     let (header, encoding, version) = Header::from_string(format!("<!-- DMXVersion {encoding} -->")).unwrap();
+    ///     assert!(header.is_legacy());
+    ///     (header.format, header.format_version, encoding, version)
+    /// };
+    ///
+    /// assert_eq!(legacy("binary_v2"), (String::from("dmx"), 22, String::from("binary"), 1));
+    /// assert_eq!(legacy("sfm_v1"), (String::from("sfm_v1"), 1, String::from("binary"), 1));
+    /// assert_eq!(legacy("keyvalues2_v1"), (String::from("dmx"), 22, String::from("keyvalues2"), 1));
+    /// assert_eq!(legacy("keyvalues2_flat_v1"), (String::from("dmx"), 22, String::from("keyvalues2_flat"), 1));
+    /// assert!(Header::from_string(String::from("<!-- DMXVersion xml_v1 -->")).is_err());
+    /// ```
     pub fn from_string(value: String) -> Result<(Self, String, i32), FileHeaderError> {
         let trimmed_header = value.trim();
         const HEADER_START: &str = "<!-- dmx encoding ";
         const HEADER_END: &str = " -->";
         if !trimmed_header.starts_with(HEADER_START) {
-            return Self::read_legacy(value);
+            let (mut header, encoding, encoding_version) = Self::read_legacy(value)?;
+            header.encoding = Some(encoding.clone());
+            return Ok((header, encoding, encoding_version));
         }
         if !trimmed_header.ends_with(HEADER_END) {
             return Err(FileHeaderError::InvalidFileHeader);
@@ -81,18 +187,20 @@ impl Header {
         let format = tokens[3].to_string();
         let format_version = tokens[4].parse::<i32>()?;
 
-        Ok((Self::new(format, format_version), encoding, encoding_version))
+        let mut header = Self::new(format, format_version);
+        header.raw = Some(value);
+        header.encoding = Some(encoding.clone());
+        Ok((header, encoding, encoding_version))
     }
 
     fn read_legacy(value: String) -> Result<(Self, String, i32), FileHeaderError> {
         let trimmed_header = value.trim();
-        const HEADER_START: &str = "<!-- DMXVersion ";
         const HEADER_END: &str = " -->";
-        if !trimmed_header.starts_with(HEADER_START) || !trimmed_header.ends_with(HEADER_END) {
+        if !trimmed_header.starts_with(LEGACY_HEADER_START) || !trimmed_header.ends_with(HEADER_END) {
             return Err(FileHeaderError::InvalidFileHeader);
         }
 
-        let inner_tokens = &trimmed_header[HEADER_START.len()..trimmed_header.len() - HEADER_END.len()];
+        let inner_tokens = &trimmed_header[LEGACY_HEADER_START.len()..trimmed_header.len() - HEADER_END.len()];
         let tokens = inner_tokens.split_whitespace().collect::<Vec<_>>();
         if tokens.len() != 1 {
             return Err(FileHeaderError::InvalidFileHeader);
@@ -102,8 +210,9 @@ impl Header {
         if legacy_encoding.starts_with("binary_v") {
             return Ok((
                 Self {
-                    format: String::from(CURRENT_ENCODING),
-                    format_version: CURRENT_FORMAT_VERSION,
+                    raw: Some(value.clone()),
+                    style: HeaderStyle::Legacy,
+                    ..Self::new(String::from(CURRENT_ENCODING), CURRENT_FORMAT_VERSION)
                 },
                 String::from("binary"),
                 1,
@@ -113,8 +222,9 @@ impl Header {
         if legacy_encoding.starts_with("sfm_v") {
             return Ok((
                 Self {
-                    format: String::from(legacy_encoding),
-                    format_version: 1,
+                    raw: Some(value.clone()),
+                    style: HeaderStyle::Legacy,
+                    ..Self::new(String::from(legacy_encoding), 1)
                 },
                 String::from("binary"),
                 1,
@@ -124,8 +234,9 @@ impl Header {
         if legacy_encoding.starts_with("keyvalues2_v") {
             return Ok((
                 Self {
-                    format: String::from(CURRENT_ENCODING),
-                    format_version: CURRENT_FORMAT_VERSION,
+                    raw: Some(value.clone()),
+                    style: HeaderStyle::Legacy,
+                    ..Self::new(String::from(CURRENT_ENCODING), CURRENT_FORMAT_VERSION)
                 },
                 String::from("keyvalues2"),
                 1,
@@ -135,8 +246,9 @@ impl Header {
         if legacy_encoding.starts_with("keyvalues2_flat_v") {
             return Ok((
                 Self {
-                    format: String::from(CURRENT_ENCODING),
-                    format_version: CURRENT_FORMAT_VERSION,
+                    raw: Some(value.clone()),
+                    style: HeaderStyle::Legacy,
+                    ..Self::new(String::from(CURRENT_ENCODING), CURRENT_FORMAT_VERSION)
                 },
                 String::from("keyvalues2_flat"),
                 1,
@@ -146,6 +258,64 @@ impl Header {
         Err(FileHeaderError::UnknownLegacyEncoding(legacy_encoding.to_string()))
     }
 
+    /// Returns if the header was parsed from a legacy `<!-- DMXVersion -->` header.
+    ///
+    /// Headers made with [Header::new] or [Header::default] are never legacy, whatever their format version is.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Header;
+    ///
+    /// let (mut header, _, _) = Header::from_string(String::from("<!-- DMXVersion binary_v2 -->")).unwrap();
+    /// assert!(header.is_legacy());
+    /// header.clear_raw();
+    /// assert!(header.is_legacy());
+    ///
+    /// assert!(!Header::new("dmx", -1).is_legacy());
+    /// ```
+    pub fn is_legacy(&self) -> bool {
+        self.style == HeaderStyle::Legacy
+    }
+
+    /// Returns the encoding name the header was parsed with, set by [Header::from_string] and [Header::from_buffer].
+    ///
+    /// This is kept after [Header::clear_raw], and is [None] for headers made with [Header::new] or [Header::default].
+    pub fn encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    /// Returns if the header was read from a file with a binary or text encoding.
+    ///
+    /// This is [None] for headers that weren't parsed or have an unknown encoding, see [Header::encoding].
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::{EncodingKind, Header};
+    ///
+    /// let (mut header, _, _) = Header::from_string(String::from("<!-- dmx encoding keyvalues2 4 format model 22 -->")).unwrap();
+    /// header.clear_raw();
+    /// assert_eq!(header.encoding(), Some("keyvalues2"));
+    /// assert_eq!(header.encoding_kind(), Some(EncodingKind::Text));
+    /// assert!(header.is_text());
+    ///
+    /// let (legacy, _, _) = Header::from_string(String::from("<!-- DMXVersion binary_v2 -->")).unwrap();
+    /// assert!(legacy.is_binary());
+    /// assert_eq!(Header::default().encoding_kind(), None);
+    /// ```
+    pub fn encoding_kind(&self) -> Option<EncodingKind> {
+        EncodingKind::from_encoding(self.encoding.as_deref()?)
+    }
+
+    /// Returns if the header was read from a file with a binary encoding, see [Header::encoding_kind].
+    pub fn is_binary(&self) -> bool {
+        self.encoding_kind() == Some(EncodingKind::Binary)
+    }
+
+    /// Returns if the header was read from a file with a text encoding, see [Header::encoding_kind].
+    pub fn is_text(&self) -> bool {
+        self.encoding_kind() == Some(EncodingKind::Text)
+    }
+
     /// Parses a [Header] from a buffer.
     ///
     /// # Returns
@@ -158,19 +328,132 @@ impl Header {
 
     /// Creates a proper DMX file header.
     ///
+    /// The header is split by whitespace when parsed, so any whitespace in the encoding or format is replaced with underscores.
+    ///
+    /// If [Header::set_verbatim] is on and [Header::raw] parses to the same format, encoding, and versions, it is returned instead.
+    ///
     /// # Example
     /// ```text
     /// <!-- dmx encoding {encoding} {encoding_version} format {format} {format_version} -->
     /// ```
     pub fn create_header(&self, encoding: &str, encoding_version: i32) -> String {
+        if let Some(raw) = self.raw.as_ref().filter(|_| self.verbatim) {
+            match Self::from_string(raw.clone()) {
+                Ok((header, raw_encoding, raw_encoding_version))
+                    if header.format == self.format
+                        && header.format_version == self.format_version
+                        && raw_encoding == encoding
+                        && raw_encoding_version == encoding_version =>
+                {
+                    return if raw.ends_with('\n') { raw.clone() } else { format!("{}\n", raw) };
+                }
+                _ => {}
+            }
+        }
+
+        fn header_token(value: &str) -> String {
+            value.chars().map(|character| if character.is_whitespace() { '_' } else { character }).collect()
+        }
+
         format!(
             "<!-- dmx encoding {} {} format {} {} -->\n",
-            encoding, encoding_version, self.format, self.format_version
+            header_token(encoding),
+            encoding_version,
+            header_token(&self.format),
+            self.format_version
         )
     }
+
+    /// Returns a value that formats as the header line [Header::create_header] would create with the encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use datamodel::Header;
+    ///
+    /// let (header, encoding, version) = Header::from_string(String::from("<!-- dmx encoding binary 5 format model 18 -->")).unwrap();
+    /// assert_eq!(header, Header::new("model", 18));
+    /// assert_eq!(header.display(&encoding, version).to_string(), "<!-- dmx encoding binary 5 format model 18 -->\n");
+    /// ```
+    pub fn display<'a>(&'a self, encoding: &'a str, encoding_version: i32) -> HeaderDisplay<'a> {
+        HeaderDisplay {
+            header: self,
+            encoding,
+            encoding_version,
+        }
+    }
+}
+
+/// Formats a [Header] with an encoding, returned by [Header::display].
+#[derive(Debug, Clone, Copy)]
+pub struct HeaderDisplay<'a> {
+    header: &'a Header,
+    encoding: &'a str,
+    encoding_version: i32,
+}
+
+impl std::fmt::Display for HeaderDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.header.create_header(self.encoding, self.encoding_version))
+    }
+}
+
+/// Whether an encoding stores the data as binary or as text.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EncodingKind {
+    /// The `binary` encoding.
+    Binary,
+    /// The `keyvalues2`, `keyvalues2_flat`, `xml`, and `xml_flat` encodings.
+    Text,
+}
+
+impl EncodingKind {
+    /// Returns the kind of a supported encoding name, or [None] if the encoding isn't supported.
+    pub fn from_encoding(encoding: &str) -> Option<Self> {
+        match encoding {
+            "binary" => Some(Self::Binary),
+            "keyvalues2" | "keyvalues2_flat" | "xml" | "xml_flat" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Returns if an encoding name is the binary encoding, see [EncodingKind::from_encoding].
+///
+/// # Example
+/// ```
+/// assert!(datamodel::is_binary_encoding("binary"));
+/// assert!(!datamodel::is_binary_encoding("keyvalues2"));
+/// assert!(!datamodel::is_binary_encoding("unknown"));
+/// ```
+pub fn is_binary_encoding(name: &str) -> bool {
+    EncodingKind::from_encoding(name) == Some(EncodingKind::Binary)
+}
+
+/// Reads the encoding name and version from the header line at the start of the bytes.
+///
+/// Only the bytes up to the first newline are read, so a prefix of a file is enough.
+/// Returns [None] if the header isn't a valid DMX header, which includes files that aren't DMX at all.
+///
+/// # Example
+/// ```
+/// let prefix = b"<!-- dmx encoding binary 9 format model 22 -->\n\0...";
+/// assert_eq!(datamodel::detect_encoding(prefix), Some((String::from("binary"), 9)));
+/// assert_eq!(datamodel::detect_encoding(b"PK\x03\x04"), None);
+/// ```
+pub fn detect_encoding(prefix: &[u8]) -> Option<(String, i32)> {
+    let header_line = match prefix.iter().position(|&byte| byte == b'\n') {
+        Some(position) => &prefix[..position],
+        None => prefix,
+    };
+    let (_, encoding, version) = Header::from_string(String::from_utf8_lossy(header_line).into_owned()).ok()?;
+    Some((encoding, version))
 }
 
 /// An error returned by [deserialize].
+///
+/// # Compatibility
+/// The [SerializationError::Xml] variant was added for the `xml` encodings,
+/// so a `match` that listed every variant without a wildcard arm needs an arm for it.
 #[derive(Debug, ThisError)]
 pub enum SerializationError {
     #[error("Unknown Encoding")]
@@ -181,6 +464,12 @@ pub enum SerializationError {
     Binary(#[from] BinarySerializationError),
     #[error("KeyValues2 Serialization Error: {0}")]
     KeyValues2(#[from] KeyValues2SerializationError),
+    #[error("XML Serialization Error: {0}")]
+    Xml(#[from] XmlSerializationError),
+    #[error("IO Error: {0}")]
+    Io(#[from] Error),
+    #[error("Data Is Gzip Compressed But The gzip Feature Is Disabled")]
+    CompressedUnsupported,
 }
 
 /// Deserialize a buffer with Valve Serializers.
@@ -189,6 +478,9 @@ pub enum SerializationError {
 ///
 /// Supports legacy headers.
 ///
+/// Buffers that start with the gzip magic bytes are decompressed first with the `gzip` feature,
+/// without it [SerializationError::CompressedUnsupported] is returned.
+///
 /// # Returns
 /// The parsed [Header] and the root [Element] from the buffer.
 ///
@@ -196,18 +488,389 @@ pub enum SerializationError {
 /// - `binary` with [BinarySerializer]
 /// - `keyvalues2` with [KeyValues2Serializer]
 /// - `keyvalues2_flat` with [KeyValues2FlatSerializer]
+/// - `xml` with [XmlSerializer]
+/// - `xml_flat` with [XmlFlatSerializer]
+///
+/// # Example
+/// Loading a file with a legacy header.
+/// ```
+/// let file = b"<!-- DMXVersion keyvalues2_v1 -->
+/// \"DmElement\"
+/// {
+///     \"id\" \"elementid\" \"6f0c0f26-3e2b-4e4c-9ad4-2b5b1e6f4d7a\"
+///     \"name\" \"string\" \"root\"
+/// }
+/// ";
+/// let (header, root) = datamodel::deserialize(&mut &file[..]).unwrap();
+/// assert_eq!(header.format, "dmx");
+/// assert!(header.is_legacy());
+/// assert_eq!(&*root.get_class(), "DmElement");
+/// assert!(root.get_attribute("name").is_some());
+/// ```
+///
+/// Loading a gzip compressed file with the `gzip` feature.
+/// ```
+/// # #[cfg(feature = "gzip")]
+/// # {
+/// use std::io::{BufReader, Write};
+///
+/// use datamodel::{Element, Header, Serializer, serializers::BinarySerializer};
+///
+/// let mut file = Vec::new();
+/// BinarySerializer::serialize(&mut file, &Header::default(), &Element::named("root")).unwrap();
+/// let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+/// encoder.write_all(&file).unwrap();
+/// let compressed = encoder.finish().unwrap();
+///
+/// // The magic bytes are found even when the reader buffers one byte at a time.
+/// let (_, root) = datamodel::deserialize(&mut BufReader::with_capacity(1, compressed.as_slice())).unwrap();
+/// assert_eq!(root.name_owned().as_deref(), Some("root"));
+/// # }
+/// ```
+///
+/// Without the `gzip` feature compressed files are rejected.
+/// ```
+/// # #[cfg(not(feature = "gzip"))]
+/// # {
+/// use datamodel::SerializationError;
+///
+/// let compressed = [0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF];
+/// let result = datamodel::deserialize_slice(&compressed);
+/// assert!(matches!(result, Err(SerializationError::CompressedUnsupported)));
+/// # }
+/// ```
 pub fn deserialize(buffer: &mut impl BufRead) -> Result<(Header, Element), SerializationError> {
-    let (header, encoding, version) = Header::from_buffer(buffer)?;
+    let (header, _, _, root) = deserialize_full(buffer)?;
+    Ok((header, root))
+}
+
+/// Deserialize a buffer with Valve Serializers, keeping the encoding of the file.
+///
+/// This is the same as [deserialize], but also returns the encoding name and version from the header,
+/// so a loaded file can be saved again with the same encoding and version.
+///
+/// # Returns
+/// The parsed [Header], the encoding name, the encoding version, and the root [Element] from the buffer.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, serializers::BinarySerializer};
+///
+/// let mut file = Vec::new();
+/// BinarySerializer::serialize_version(&mut file, &Header::default(), &Element::named("root"), 4).unwrap();
+///
+/// let (header, encoding, version, root) = datamodel::deserialize_full(&mut file.as_slice()).unwrap();
+/// assert_eq!((encoding.as_str(), version), ("binary", 4));
+///
+/// let mut saved = Vec::new();
+/// BinarySerializer::serialize_version(&mut saved, &header, &root, version).unwrap();
+/// assert_eq!(saved, file);
+/// ```
+pub fn deserialize_full(buffer: &mut impl BufRead) -> Result<(Header, String, i32, Element), SerializationError> {
+    let mut document = deserialize_uncompressed(&mut decompressed(buffer)?, ReadMode::Root)?;
+    Ok((document.header, document.encoding, document.version, document.elements.swap_remove(0)))
+}
+
+/// Returns the buffer, decompressed if it starts with the gzip magic bytes.
+fn decompressed<'a>(buffer: &'a mut impl BufRead) -> Result<Box<dyn BufRead + 'a>, SerializationError> {
+    // The buffer can hold fewer bytes than the magic, so the start is read out and put back in front of the rest.
+    let mut magic = [0; GZIP_MAGIC.len()];
+    let mut magic_length = 0;
+    while magic_length < magic.len() {
+        let available = buffer.fill_buf()?;
+        if available.is_empty() {
+            break;
+        }
+        let count = available.len().min(magic.len() - magic_length);
+        magic[magic_length..magic_length + count].copy_from_slice(&available[..count]);
+        buffer.consume(count);
+        magic_length += count;
+    }
+    let buffer = Read::chain(Read::take(Cursor::new(magic), magic_length as u64), buffer);
+
+    if magic == GZIP_MAGIC {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(BufReader::new(GzDecoder::new(buffer))));
+        #[cfg(not(feature = "gzip"))]
+        return Err(SerializationError::CompressedUnsupported);
+    }
+
+    Ok(Box::new(buffer))
+}
+
+/// What [deserialize_uncompressed] reads from a document.
+#[derive(Clone, Copy)]
+enum ReadMode {
+    /// Only the root.
+    Root,
+    /// Every element, see [deserialize_arena].
+    Arena,
+    /// The top level elements of one document in a stream of documents, see [deserialize_all].
+    Document,
+}
+
+/// A document read by [deserialize_uncompressed].
+struct ReadDocument {
+    header: Header,
+    encoding: String,
+    version: i32,
+    /// The elements with the root first.
+    elements: Vec<Element>,
+    /// The length of the document with its header if the buffer was read past its end.
+    length: Option<usize>,
+}
+
+/// Reads the header and the elements of a document with the serializer of its encoding.
+fn deserialize_uncompressed(buffer: &mut impl BufRead, mode: ReadMode) -> Result<ReadDocument, SerializationError> {
+    let mut header_line = Vec::new();
+    buffer.read_until(b'\n', &mut header_line).map_err(FileHeaderError::from)?;
+    let (header, encoding, version) = Header::from_string(String::from_utf8_lossy(&header_line).into_owned())?;
+
+    let (elements, length) = match (encoding.as_str(), mode) {
+        ("binary", ReadMode::Root) => (vec![BinarySerializer::deserialize(buffer, encoding.clone(), version)?], None),
+        ("binary", ReadMode::Arena) => (BinarySerializer::deserialize_table(buffer, encoding.clone(), version)?, None),
+        ("binary", ReadMode::Document) => {
+            let mut elements = BinarySerializer::deserialize_table(buffer, encoding.clone(), version)?;
+            elements.truncate(1);
+            (elements, None)
+        }
+        ("keyvalues2", ReadMode::Root | ReadMode::Arena) => (vec![KeyValues2Serializer::deserialize(buffer, encoding.clone(), version)?], None),
+        ("keyvalues2_flat", ReadMode::Root | ReadMode::Arena) => (vec![KeyValues2FlatSerializer::deserialize(buffer, encoding.clone(), version)?], None),
+        ("keyvalues2" | "keyvalues2_flat", ReadMode::Document) => {
+            KeyValues2Serializer::deserialize_document(buffer, String::from(KeyValues2Serializer::name()), version)?
+        }
+        ("xml", ReadMode::Root | ReadMode::Arena) => (vec![XmlSerializer::deserialize(buffer, encoding.clone(), version)?], None),
+        ("xml_flat", ReadMode::Root | ReadMode::Arena) => (vec![XmlFlatSerializer::deserialize(buffer, encoding.clone(), version)?], None),
+        ("xml" | "xml_flat", ReadMode::Document) => {
+            let (root, length) = XmlSerializer::deserialize_document(buffer, encoding.clone(), version)?;
+            (vec![root], Some(length))
+        }
+        _ => return Err(SerializationError::UnknownEncoding),
+    };
+
+    Ok(ReadDocument {
+        header,
+        encoding,
+        version,
+        elements,
+        length: length.map(|length| header_line.len() + length),
+    })
+}
+
+/// Deserialize a byte slice with Valve Serializers.
+///
+/// This is the same as [deserialize] for data that is already in memory, such as from [include_bytes].
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, serializers::BinarySerializer};
+///
+/// let mut file = Vec::new();
+/// BinarySerializer::serialize(&mut file, &Header::new("model", 18), &Element::named("root")).unwrap();
+///
+/// let (header, root) = datamodel::deserialize_slice(&file).unwrap();
+/// assert_eq!(header, Header::new("model", 18));
+/// assert_eq!(root.name_owned().as_deref(), Some("root"));
+/// ```
+pub fn deserialize_slice(mut data: &[u8]) -> Result<(Header, Element), SerializationError> {
+    deserialize(&mut data)
+}
+
+/// Deserialize an async stream with Valve Serializers.
+///
+/// The stream is read to the end asynchronously, then parsed the same as [deserialize_slice].
+///
+/// # Example
+/// ```no_run
+/// async fn load(stream: &mut (impl tokio::io::AsyncRead + Unpin)) -> Result<datamodel::Element, datamodel::SerializationError> {
+///     let (_, root) = datamodel::deserialize_async(stream).await?;
+///     Ok(root)
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub async fn deserialize_async(stream: &mut (impl AsyncRead + Unpin)) -> Result<(Header, Element), SerializationError> {
+    let mut data = Vec::new();
+    stream.read_to_end(&mut data).await?;
+    deserialize_slice(&data)
+}
+
+/// Encodes a root element with a serializer to an async stream.
+///
+/// The file is encoded to memory with [Serializer::serialize], then written to the stream asynchronously.
+#[cfg(feature = "async")]
+pub async fn serialize_async<S: Serializer>(stream: &mut (impl AsyncWrite + Unpin), header: &Header, root: &Element) -> Result<(), S::Error>
+where
+    S::Error: From<Error>,
+{
+    let mut data = Vec::new();
+    S::serialize(&mut data, header, root)?;
+    stream.write_all(&data).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Deserialize a buffer with Valve Serializers into a flat list of elements.
+///
+/// For `binary` the list is the element table of the file in order, including elements nothing references.
+/// For the text encodings it's every element reachable from the root, in the order they are reached.
+/// The elements are still connected by their attributes, the list is for building indices without walking the graph.
+/// The buffer is read the same as [deserialize], including gzip compressed buffers.
+///
+/// # Returns
+/// The parsed [Header], the elements, and the index of the root element in the elements.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, serializers::{BinarySerializer, KeyValues2Serializer}};
+///
+/// let mut root = Element::named("root");
+/// root.link("child", &Element::named("child"));
+///
+/// let mut binary = Vec::new();
+/// BinarySerializer::serialize(&mut binary, &Header::default(), &root).unwrap();
+/// let mut text = Vec::new();
+/// KeyValues2Serializer::serialize(&mut text, &Header::default(), &root).unwrap();
+///
+/// for file in [binary, text] {
+///     let (_, elements, root_index) = datamodel::deserialize_arena(&mut file.as_slice()).unwrap();
+///     let names = elements.iter().map(|element| element.name_owned().unwrap()).collect::<Vec<_>>();
+///     assert_eq!(names, ["root", "child"]);
+///     assert_eq!(*elements[root_index].get_id(), *root.get_id());
+/// }
+/// ```
+///
+/// Loading a gzip compressed file with the `gzip` feature.
+/// ```
+/// # #[cfg(feature = "gzip")]
+/// # {
+/// use std::io::Write;
+///
+/// use datamodel::{Element, Header, Serializer, serializers::BinarySerializer};
+///
+/// let mut file = Vec::new();
+/// BinarySerializer::serialize(&mut file, &Header::default(), &Element::named("root")).unwrap();
+/// let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+/// encoder.write_all(&file).unwrap();
+/// let compressed = encoder.finish().unwrap();
+///
+/// let (_, elements, root_index) = datamodel::deserialize_arena(&mut compressed.as_slice()).unwrap();
+/// assert_eq!(elements[root_index].name_owned().as_deref(), Some("root"));
+/// # }
+/// ```
+pub fn deserialize_arena(buffer: &mut impl BufRead) -> Result<(Header, Vec<Element>, usize), SerializationError> {
+    let mut document = deserialize_uncompressed(&mut decompressed(buffer)?, ReadMode::Arena)?;
+    if document.encoding != BinarySerializer::name() {
+        document.elements = document.elements[0].reachable_elements().into_iter().collect();
+    }
+    Ok((document.header, document.elements, 0))
+}
+
+/// Deserialize a buffer of one or more DMX documents written one after another.
+///
+/// Each document starts with its header line.
+/// A binary document ends after its data and an `xml` document after its closing `</dmx>` tag.
+/// A `keyvalues2` document ends at a header line between its top level elements,
+/// a line that looks like a header inside a string is part of the string.
+/// Whitespace after the last document is ignored.
+/// A stream that starts with the gzip magic bytes is decompressed first, the same as [deserialize].
+///
+/// # Returns
+/// The parsed [Header] of each document with its top level elements.
+/// For the `keyvalues2` encodings that's every element at the top level of the document, see [KeyValues2Serializer::deserialize_all],
+/// the other encodings only have the root at the top level.
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, serializers::{BinarySerializer, KeyValues2Serializer}};
+///
+/// let mut stream = Vec::new();
+/// BinarySerializer::serialize(&mut stream, &Header::default(), &Element::named("first")).unwrap();
+/// KeyValues2Serializer::serialize(&mut stream, &Header::default(), &Element::named("second")).unwrap();
+///
+/// let documents = datamodel::deserialize_all(&mut stream.as_slice()).unwrap();
+/// let names = documents.iter().flat_map(|(_, elements)| elements.iter().map(|element| element.name_owned().unwrap())).collect::<Vec<_>>();
+/// assert_eq!(names, ["first", "second"]);
+/// ```
+///
+/// A string can hold a line that looks like a header.
+/// ```
+/// use datamodel::{Element, Header, Serializer, attribute::AttributeInfo, serializers::{KeyValues2Serializer, XmlSerializer}};
+///
+/// let mut first = Element::named("first");
+/// first.set_attribute("notes", String::from("copied from\n<!-- dmx encoding binary 9 format dmx 22 -->\n").into_attribute());
+/// let mut stream = Vec::new();
+/// KeyValues2Serializer::serialize(&mut stream, &Header::default(), &first).unwrap();
+/// XmlSerializer::serialize(&mut stream, &Header::default(), &Element::named("second")).unwrap();
+/// KeyValues2Serializer::serialize(&mut stream, &Header::default(), &Element::named("third")).unwrap();
+///
+/// let documents = datamodel::deserialize_all(&mut stream.as_slice()).unwrap();
+/// assert_eq!(documents.len(), 3);
+/// let notes = documents[0].1[0].get_attribute("notes").unwrap();
+/// assert_eq!(notes.as_str().as_deref(), Some("copied from\n<!-- dmx encoding binary 9 format dmx 22 -->\n"));
+/// assert_eq!(documents[1].1[0].name_owned().as_deref(), Some("second"));
+/// assert_eq!(documents[2].1[0].name_owned().as_deref(), Some("third"));
+/// ```
+///
+/// Loading a gzip compressed stream with the `gzip` feature.
+/// ```
+/// # #[cfg(feature = "gzip")]
+/// # {
+/// use std::io::Write;
+///
+/// use datamodel::{Element, Header, Serializer, serializers::{BinarySerializer, XmlSerializer}};
+///
+/// let mut stream = Vec::new();
+/// XmlSerializer::serialize(&mut stream, &Header::default(), &Element::named("first")).unwrap();
+/// BinarySerializer::serialize(&mut stream, &Header::default(), &Element::named("second")).unwrap();
+/// let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+/// encoder.write_all(&stream).unwrap();
+/// let compressed = encoder.finish().unwrap();
+///
+/// let documents = datamodel::deserialize_all(&mut compressed.as_slice()).unwrap();
+/// assert_eq!(documents.len(), 2);
+/// assert_eq!(documents[1].1[0].name_owned().as_deref(), Some("second"));
+/// # }
+/// ```
+pub fn deserialize_all(buffer: &mut impl BufRead) -> Result<Vec<(Header, Vec<Element>)>, SerializationError> {
+    let mut data = Vec::new();
+    decompressed(buffer)?.read_to_end(&mut data).map_err(FileHeaderError::from)?;
+    let mut remaining = data.as_slice();
+    let mut documents = Vec::new();
 
-    match encoding.as_str() {
-        "binary" => Ok((header, BinarySerializer::deserialize(buffer, encoding, version)?)),
-        "keyvalues2" => Ok((header, KeyValues2Serializer::deserialize(buffer, encoding, version)?)),
-        "keyvalues2_flat" => Ok((header, KeyValues2FlatSerializer::deserialize(buffer, encoding, version)?)),
-        _ => Err(SerializationError::UnknownEncoding),
+    while !remaining.trim_ascii_start().is_empty() {
+        let document = remaining.trim_ascii_start();
+        remaining = document;
+        let read = deserialize_uncompressed(&mut remaining, ReadMode::Document)?;
+        if let Some(length) = read.length {
+            remaining = &document[length..];
+        }
+        documents.push((read.header, read.elements));
     }
+
+    Ok(documents)
 }
 
 /// The trait allows for serialize and deserialize of a buffer for a root element from an encoding.
+///
+/// The encoding in the written header always comes from the serializer, [Header] only holds the format.
+/// A verbatim [Header::raw] line from a file with another encoding isn't reused, see [Header::create_header].
+///
+/// # Example
+/// ```
+/// use datamodel::{Element, Header, Serializer, serializers::{BinarySerializer, KeyValues2Serializer, XmlSerializer}};
+///
+/// fn written_encoding<S: Serializer>(header: &Header) -> (String, i32) {
+///     let mut buffer = Vec::new();
+///     S::serialize(&mut buffer, header, &Element::default()).ok().unwrap();
+///     let (_, encoding, version) = Header::from_buffer(&mut buffer.as_slice()).unwrap();
+///     (encoding, version)
+/// }
+///
+/// let (header, _, _) = Header::from_string(String::from("<!-- dmx encoding keyvalues2 4 format model 22 -->")).unwrap();
+/// assert_eq!(written_encoding::<BinarySerializer>(&header), (String::from(BinarySerializer::name()), BinarySerializer::version()));
+/// assert_eq!(written_encoding::<KeyValues2Serializer>(&header), (String::from(KeyValues2Serializer::name()), KeyValues2Serializer::version()));
+/// assert_eq!(written_encoding::<XmlSerializer>(&header), (String::from(XmlSerializer::name()), XmlSerializer::version()));
+/// ```
 pub trait Serializer {
     /// The error type that serialize_version and deserialize might return.
     type Error;
@@ -216,6 +879,12 @@ pub trait Serializer {
     fn name() -> &'static str;
     /// The current version of the encoding.
     fn version() -> i32;
+    /// The conventional file extension for files written by the serializer, without the leading dot.
+    ///
+    /// DMX files use `dmx` no matter the encoding, the encoding is read from the header instead of the extension.
+    fn file_extension() -> &'static str {
+        "dmx"
+    }
     /// Encodes a root element to a buffer with a selected version.
     ///
     /// The implementation must check the passed in version if its valid.
@@ -228,4 +897,12 @@ pub trait Serializer {
     ///
     /// The implementation must check the passed in encoding and version are valid and must handle the file header that might exist.
     fn deserialize(buffer: &mut impl BufRead, encoding: String, version: i32) -> Result<Element, Self::Error>;
+    /// Returns if an attribute type can be serialized with a version of the encoding.
+    ///
+    /// This lets callers check a model before serializing instead of handling the error after.
+    /// The default supports every attribute type for versions between 1 and [Serializer::version].
+    fn supports(attribute_type: AttributeType, version: i32) -> bool {
+        let _ = attribute_type;
+        (1..=Self::version()).contains(&version)
+    }
 }
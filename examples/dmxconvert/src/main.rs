@@ -7,7 +7,10 @@ use std::{
 use clap::Parser;
 use datamodel::{
     SerializationError, Serializer,
-    serializers::{BinarySerializationError, BinarySerializer, KeyValues2FlatSerializer, KeyValues2SerializationError, KeyValues2Serializer},
+    serializers::{
+        BinarySerializationError, BinarySerializer, KeyValues2FlatSerializer, KeyValues2SerializationError, KeyValues2Serializer, XmlFlatSerializer,
+        XmlSerializationError, XmlSerializer,
+    },
 };
 use thiserror::Error as ThisError;
 
@@ -23,6 +26,8 @@ enum ConvertDMXError {
     BinaryError(#[from] BinarySerializationError),
     #[error("Out File Failed To Serialize: {0}")]
     KeyValues2Error(#[from] KeyValues2SerializationError),
+    #[error("Out File Failed To Serialize: {0}")]
+    XmlError(#[from] XmlSerializationError),
     #[error("Unknown Out File Encoding: {0}")]
     UnknownEncoding(String),
 }
@@ -39,12 +44,12 @@ struct ConvertDMXArguments {
     out_file: Option<PathBuf>,
 
     /// Specify the encoding for the conversion.
-    /// Valid encodings are: binary - keyvalues2 - keyvalues2_flat
+    /// Valid encodings are: binary - keyvalues2 - keyvalues2_flat - xml - xml_flat
     #[arg(short, long)]
     encoding: Option<String>,
 
     /// Specify the encoding version for the conversion.
-    /// Valid version are: binary 1..5 - keyvalues2 1 - keyvalues2_flat 1
+    /// Valid version are: binary 1..5 - keyvalues2 1 - keyvalues2_flat 1 - xml 1 - xml_flat 1
     #[arg(long)]
     encoding_version: Option<i32>,
 }
@@ -115,5 +120,13 @@ fn main() {
         );
     }
 
+    if out_encoding == XmlSerializer::name() {
+        supported_serializer!(XmlSerializer, XmlError, out_file_buffer, header, root, arguments.encoding_version);
+    }
+
+    if out_encoding == XmlFlatSerializer::name() {
+        supported_serializer!(XmlFlatSerializer, XmlError, out_file_buffer, header, root, arguments.encoding_version);
+    }
+
     eprint!("{}", ConvertDMXError::UnknownEncoding(out_encoding));
 }